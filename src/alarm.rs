@@ -0,0 +1,75 @@
+//! An alarm that repeats the phase-end sound, louder each time, until it is acknowledged.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::sound::SoundEvent;
+
+/// Number of repeats after which the alarm plays at full volume.
+pub const ESCALATION_STEPS: u32 = 5;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmConfig {
+    pub enabled: bool,
+    /// Seconds between repeats.
+    pub interval_seconds: u32,
+    /// Minutes after which the alarm stops repeating, even if not acknowledged.
+    pub max_minutes: u32,
+}
+
+impl Default for AlarmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_seconds: 10,
+            max_minutes: 5,
+        }
+    }
+}
+
+impl AlarmConfig {
+    /// Returns how many repeats should have played `elapsed` after the phase ended,
+    /// not counting the sound played at the end itself.
+    pub fn repeats_due(&self, elapsed: Duration) -> u32 {
+        let elapsed = elapsed.min(Duration::from_secs(self.max_minutes as u64 * 60));
+        (elapsed.as_secs() / self.interval_seconds.max(1) as u64) as u32
+    }
+
+    /// Returns whether the alarm has stopped repeating `elapsed` after the phase ended.
+    pub fn is_expired(&self, elapsed: Duration) -> bool {
+        elapsed >= Duration::from_secs(self.max_minutes as u64 * 60)
+    }
+}
+
+/// Raises `volume` towards full volume over the first `ESCALATION_STEPS` repeats.
+pub fn escalated_volume(volume: f32, repeat: u32) -> f32 {
+    let progress = (repeat as f32 / ESCALATION_STEPS as f32).min(1.0);
+    volume + (1.0 - volume).max(0.0) * progress
+}
+
+/// A ringing alarm waiting to be acknowledged.
+#[derive(Debug, Clone)]
+pub struct Alarm {
+    pub event: SoundEvent,
+    /// Record of the phase that ended, which stores how long the acknowledgement took.
+    pub record_id: Option<String>,
+    pub started: Instant,
+    /// Repeats played so far.
+    pub repeats: u32,
+    /// Whether the window has asked the system for the user's attention.
+    pub attention_requested: bool,
+}
+
+impl Alarm {
+    pub fn new(event: SoundEvent, record_id: Option<String>) -> Self {
+        Self {
+            event,
+            record_id,
+            started: Instant::now(),
+            repeats: 0,
+            attention_requested: false,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Ambient sound played during pomodoros: generated noise and looping audio files.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+
+use crate::sound::Sound;
+
+const NOISE_SAMPLE_RATE: u32 = 44_100;
+/// Time to fade between full volume and silence.
+pub const FADE_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoiseColor {
+    White,
+    Pink,
+    Brown,
+}
+
+impl NoiseColor {
+    pub const ALL: [NoiseColor; 3] = [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseColor::White => "White noise",
+            NoiseColor::Pink => "Pink noise",
+            NoiseColor::Brown => "Brown noise",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmbientSource {
+    Noise(NoiseColor),
+    /// Audio file played in a loop.
+    File(PathBuf),
+}
+
+impl AmbientSource {
+    pub fn name(&self) -> String {
+        match self {
+            AmbientSource::Noise(color) => color.name().to_string(),
+            AmbientSource::File(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AmbientLayer {
+    pub source: AmbientSource,
+    pub volume: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmbientConfig {
+    pub enabled: bool,
+    pub layers: Vec<AmbientLayer>,
+    /// Fraction of the volume kept during pauses and breaks; 0 stops the sound.
+    pub duck_volume: f32,
+}
+
+impl Default for AmbientConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            layers: vec![AmbientLayer {
+                source: AmbientSource::Noise(NoiseColor::Brown),
+                volume: 0.5,
+            }],
+            duck_volume: 0.0,
+        }
+    }
+}
+
+/// A layer ready for the audio thread, with its file already loaded.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AmbientTrack {
+    Noise(NoiseColor),
+    File(Sound),
+}
+
+impl AmbientTrack {
+    /// Returns an endless source of the track.
+    pub fn source(&self) -> Result<Box<dyn Source<Item = f32> + Send>, String> {
+        match self {
+            AmbientTrack::Noise(color) => Ok(Box::new(Noise::new(*color))),
+            AmbientTrack::File(sound) => Ok(Box::new(sound.looped_source()?.convert_samples())),
+        }
+    }
+}
+
+/// Moves `current` towards `target` by at most `step`.
+pub fn approach(current: f32, target: f32, step: f32) -> f32 {
+    if current < target { (current + step).min(target) } else { (current - step).max(target) }
+}
+
+/// Endless mono noise.
+pub struct Noise {
+    color: NoiseColor,
+    state: u64,
+    /// Filter state: the pink noise filter poles, or the running sum of brown noise in the first element.
+    filter: [f32; 7],
+}
+
+impl Noise {
+    pub fn new(color: NoiseColor) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+        Self { color, state: seed | 1, filter: [0.0; 7] }
+    }
+
+    /// Returns a uniformly distributed sample in -1..1 from a xorshift generator.
+    fn white(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+impl Iterator for Noise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let white = self.white();
+        let b = &mut self.filter;
+        let sample = match self.color {
+            NoiseColor::White => white * 0.5,
+            // Paul Kellet's refined pink noise filter.
+            NoiseColor::Pink => {
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                pink * 0.11
+            }
+            // Leaky integration of white noise.
+            NoiseColor::Brown => {
+                b[0] = (b[0] + 0.02 * white) / 1.02;
+                b[0] * 3.5
+            }
+        };
+        Some(sample.clamp(-1.0, 1.0))
+    }
+}
+
+impl Source for Noise {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        NOISE_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
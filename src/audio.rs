@@ -0,0 +1,379 @@
+//! A long-lived audio service that keeps one output stream open and plays sounds sent to it.
+//!
+//! rodio's `OutputStream` cannot leave the thread that opened it, so the service owns it on its
+//! own thread and the app talks to it through a command channel.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{Device, OutputStream, OutputStreamHandle, Sink};
+
+use crate::ambient::{self, AmbientTrack, FADE_DURATION};
+use crate::sound::{Sound, SoundSource};
+use crate::tick::TickSource;
+
+/// How often an open stream checks that its device is still the one it should play on, and how
+/// often opening a device is retried while ambient sound or ticking wait for one.
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How often volumes are updated while ambient sound fades.
+const FADE_TICK: Duration = Duration::from_millis(20);
+
+enum AudioCommand {
+    Play { source: Box<SoundSource>, volume: f32 },
+    /// Switches to the output device with this name, or the system default for `None`.
+    SetDevice(Option<String>),
+    /// Replaces the ambient layers with these tracks and volumes, crossfading the ones that changed.
+    SetAmbient(Vec<(AmbientTrack, f32)>),
+    /// Fades the ambient sound to this level, silencing it at 0.
+    SetAmbientLevel(f32),
+    /// Replaces the ticking clock, or stops it for `None`.
+    SetTicking(Option<(Box<TickSource>, f32)>),
+}
+
+/// What the audio thread reports back to the app.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AudioStatus {
+    /// A stream is open on the named device.
+    Connected(String),
+    /// Something worth showing that did not stop playback, e.g. falling back to another device.
+    Notice(String),
+    /// No device could be opened, or ambient sound or ticking could not be played.
+    Failed(String),
+    /// An alert could not be played, so the user needs another cue.
+    AlertFailed(String),
+}
+
+/// Returns the names of the output devices of the default audio host.
+pub fn output_devices() -> Vec<String> {
+    rodio::cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Handle to the audio thread. The thread stops when the handle is dropped.
+pub struct AudioService {
+    commands: Sender<AudioCommand>,
+    statuses: Receiver<AudioStatus>,
+}
+
+impl Default for AudioService {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl AudioService {
+    /// Starts the audio thread. No device is opened until a device is set or a sound played.
+    pub fn start() -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (status_sender, statuses) = mpsc::channel();
+        thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || Output::new(status_sender).run(command_receiver))
+            .expect("failed to start the audio thread");
+        Self { commands, statuses }
+    }
+
+    /// Plays `sound` at `volume`. Decoding happens here so that a broken file is reported right away;
+    /// problems with the output device are reported later through `take_statuses`.
+    pub fn play(&self, sound: &Sound, volume: f32) -> Result<(), String> {
+        let source = sound.source()?;
+        self.send(AudioCommand::Play { source: Box::new(source), volume });
+        Ok(())
+    }
+
+    /// Opens the output device named `device`, or the system default for `None`.
+    pub fn set_device(&self, device: Option<String>) {
+        self.send(AudioCommand::SetDevice(device));
+    }
+
+    pub fn set_ambient(&self, layers: Vec<(AmbientTrack, f32)>) {
+        self.send(AudioCommand::SetAmbient(layers));
+    }
+
+    pub fn set_ambient_level(&self, level: f32) {
+        self.send(AudioCommand::SetAmbientLevel(level));
+    }
+
+    /// Starts ticking with `source` at `volume`, replacing any ticking already playing.
+    pub fn start_ticking(&self, source: TickSource, volume: f32) {
+        self.send(AudioCommand::SetTicking(Some((Box::new(source), volume))));
+    }
+
+    pub fn stop_ticking(&self) {
+        self.send(AudioCommand::SetTicking(None));
+    }
+
+    /// Returns what the audio thread reported since the last call.
+    pub fn take_statuses(&self) -> Vec<AudioStatus> {
+        self.statuses.try_iter().collect()
+    }
+
+    fn send(&self, command: AudioCommand) {
+        // The thread only stops when this handle is dropped, so sending cannot fail while it exists.
+        let _ = self.commands.send(command);
+    }
+}
+
+/// State of the audio thread.
+struct Output {
+    statuses: Sender<AudioStatus>,
+    /// The device chosen in the settings, or `None` for the system default.
+    wanted: Option<String>,
+    stream: Option<OpenStream>,
+    last_device_check: Instant,
+    ambient: Vec<AmbientSink>,
+    ambient_level: f32,
+    ambient_target: f32,
+    last_fade: Instant,
+    ticking: Option<Ticking>,
+}
+
+/// The ticking clock. Its sink is recreated whenever the stream is reopened.
+struct Ticking {
+    /// The source as it was when ticking started, from which later positions are derived.
+    source: TickSource,
+    started: Instant,
+    volume: f32,
+    sink: Option<Sink>,
+}
+
+struct OpenStream {
+    device: String,
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+/// One ambient layer. Its sink is recreated whenever the stream is reopened.
+struct AmbientSink {
+    track: AmbientTrack,
+    volume: f32,
+    /// Crossfade position of the layer itself, from 0 when added or removed to 1.
+    fade: f32,
+    removing: bool,
+    sink: Option<Sink>,
+}
+
+impl AmbientSink {
+    fn fade_target(&self) -> f32 {
+        if self.removing { 0.0 } else { 1.0 }
+    }
+}
+
+impl Output {
+    fn new(statuses: Sender<AudioStatus>) -> Self {
+        Self {
+            statuses,
+            wanted: None,
+            stream: None,
+            last_device_check: Instant::now(),
+            ambient: Vec::new(),
+            ambient_level: 0.0,
+            ambient_target: 0.0,
+            last_fade: Instant::now(),
+            ticking: None,
+        }
+    }
+
+    fn run(mut self, commands: Receiver<AudioCommand>) {
+        loop {
+            let timeout = if self.is_fading() { FADE_TICK } else { DEVICE_CHECK_INTERVAL };
+            match commands.recv_timeout(timeout) {
+                Ok(AudioCommand::Play { source, volume }) => self.play(source, volume),
+                Ok(AudioCommand::SetDevice(device)) => {
+                    self.wanted = device;
+                    self.stream = None;
+                    self.connect();
+                }
+                Ok(AudioCommand::SetAmbient(layers)) => self.set_ambient(layers),
+                Ok(AudioCommand::SetAmbientLevel(level)) => {
+                    self.ambient_target = level;
+                    if level > 0.0 && self.stream.is_none() {
+                        self.connect();
+                    }
+                }
+                Ok(AudioCommand::SetTicking(ticking)) => self.set_ticking(ticking),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            self.update_ambient();
+            if self.last_device_check.elapsed() >= DEVICE_CHECK_INTERVAL {
+                self.last_device_check = Instant::now();
+                if self.stream.is_some() {
+                    self.reconnect_if_needed();
+                } else if self.ambient_target > 0.0 || self.ticking.is_some() {
+                    self.connect();
+                }
+            }
+        }
+    }
+
+    fn set_ticking(&mut self, ticking: Option<(Box<TickSource>, f32)>) {
+        if let Some(sink) = self.ticking.take().and_then(|ticking| ticking.sink) {
+            sink.stop();
+        }
+        let Some((source, volume)) = ticking else {
+            return;
+        };
+        self.ticking = Some(Ticking { source: *source, started: Instant::now(), volume, sink: None });
+        if self.stream.is_none() {
+            // Starts the ticking sink once the stream is open.
+            self.connect();
+        } else {
+            self.start_ticking_sink();
+        }
+    }
+
+    /// Plays the ticking clock on the open stream from where it should be by now.
+    fn start_ticking_sink(&mut self) {
+        let (Some(stream), Some(ticking)) = (&self.stream, &mut self.ticking) else {
+            return;
+        };
+        if let Some(sink) = ticking.sink.take() {
+            sink.stop();
+        }
+        match Sink::try_new(&stream.handle) {
+            Ok(sink) => {
+                sink.set_volume(ticking.volume);
+                sink.append(ticking.source.after(ticking.started.elapsed()));
+                ticking.sink = Some(sink);
+            }
+            Err(err) => self.report(AudioStatus::Failed(format!("Failed to play the ticking clock: {err}"))),
+        }
+    }
+
+    fn is_fading(&self) -> bool {
+        self.ambient_level != self.ambient_target || self.ambient.iter().any(|layer| layer.fade != layer.fade_target())
+    }
+
+    /// Keeps the layers whose track is still wanted, fades out the others and fades in the new ones.
+    fn set_ambient(&mut self, mut layers: Vec<(AmbientTrack, f32)>) {
+        for layer in self.ambient.iter_mut().filter(|layer| !layer.removing) {
+            match layers.iter().position(|(track, _)| *track == layer.track) {
+                Some(index) => layer.volume = layers.remove(index).1,
+                None => layer.removing = true,
+            }
+        }
+        for (track, volume) in layers {
+            let sink = self.ambient_sink(&track);
+            self.ambient.push(AmbientSink { track, volume, fade: 0.0, removing: false, sink });
+        }
+    }
+
+    /// Advances the fades by the time passed since the last update and applies the volumes.
+    fn update_ambient(&mut self) {
+        let step = self.last_fade.elapsed().as_secs_f32() / FADE_DURATION.as_secs_f32();
+        self.last_fade = Instant::now();
+        self.ambient_level = ambient::approach(self.ambient_level, self.ambient_target, step);
+        for layer in &mut self.ambient {
+            layer.fade = ambient::approach(layer.fade, layer.fade_target(), step);
+        }
+        self.ambient.retain(|layer| !(layer.removing && layer.fade == 0.0));
+
+        let audible = self.ambient_level > 0.0 || self.ambient_target > 0.0;
+        for layer in &self.ambient {
+            if let Some(sink) = &layer.sink {
+                sink.set_volume(layer.volume * layer.fade * self.ambient_level);
+                if audible {
+                    sink.play();
+                } else {
+                    sink.pause();
+                }
+            }
+        }
+    }
+
+    /// Starts a paused, silent sink playing `track` on the open stream.
+    fn ambient_sink(&self, track: &AmbientTrack) -> Option<Sink> {
+        let stream = self.stream.as_ref()?;
+        let result = track
+            .source()
+            .and_then(|source| Sink::try_new(&stream.handle).map(|sink| (sink, source)).map_err(|err| err.to_string()));
+        match result {
+            Ok((sink, source)) => {
+                sink.pause();
+                sink.set_volume(0.0);
+                sink.append(source);
+                Some(sink)
+            }
+            Err(err) => {
+                self.report(AudioStatus::Failed(format!("Failed to play ambient sound: {err}")));
+                None
+            }
+        }
+    }
+
+    /// Plays `source` on the open stream, reconnecting once if the stream is gone.
+    fn play(&mut self, source: Box<SoundSource>, volume: f32) {
+        if self.stream.is_none() {
+            self.connect();
+        }
+        let mut sink = self.stream.as_ref().map(|stream| Sink::try_new(&stream.handle));
+        if let Some(Err(_)) = sink {
+            self.stream = None;
+            self.connect();
+            sink = self.stream.as_ref().map(|stream| Sink::try_new(&stream.handle));
+        }
+        match sink {
+            Some(Ok(sink)) => {
+                sink.set_volume(volume);
+                sink.append(*source);
+                sink.detach();
+            }
+            Some(Err(err)) => self.report(AudioStatus::AlertFailed(format!("Failed to play sound: {err}"))),
+            None => self.report(AudioStatus::AlertFailed("Failed to play sound: no audio output".to_string())),
+        }
+    }
+
+    /// Opens the wanted device, falling back to the default device if it is not connected.
+    fn connect(&mut self) {
+        let Some(device) = self.target_device() else {
+            self.report(AudioStatus::Failed("No audio output device found".to_string()));
+            return;
+        };
+        let name = device.name().unwrap_or_default();
+        if let Some(wanted) = &self.wanted
+            && *wanted != name
+        {
+            self.report(AudioStatus::Notice(format!("Audio device {wanted} is not connected, using {name}")));
+        }
+        match OutputStream::try_from_device(&device) {
+            Ok((stream, handle)) => {
+                self.report(AudioStatus::Connected(name.clone()));
+                self.stream = Some(OpenStream { device: name, _stream: stream, handle });
+                // Sinks of the previous stream went silent with it.
+                for index in 0..self.ambient.len() {
+                    self.ambient[index].sink = self.ambient_sink(&self.ambient[index].track);
+                }
+                self.start_ticking_sink();
+            }
+            Err(err) => self.report(AudioStatus::Failed(format!("Failed to open audio device {name}: {err}"))),
+        }
+    }
+
+    /// Reopens the stream when its device disappeared, or when the wanted device came back.
+    fn reconnect_if_needed(&mut self) {
+        let target = self.target_device().and_then(|device| device.name().ok());
+        if target.as_deref() != self.stream.as_ref().map(|stream| stream.device.as_str()) {
+            self.stream = None;
+            self.connect();
+        }
+    }
+
+    fn target_device(&self) -> Option<Device> {
+        let host = rodio::cpal::default_host();
+        let wanted = self.wanted.as_ref().and_then(|wanted| {
+            host.output_devices()
+                .ok()?
+                .find(|device| device.name().is_ok_and(|name| name == *wanted))
+        });
+        wanted.or_else(|| host.default_output_device())
+    }
+
+    fn report(&self, status: AudioStatus) {
+        let _ = self.statuses.send(status);
+    }
+}
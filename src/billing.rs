@@ -0,0 +1,217 @@
+//! Billable hours from completed pomodoros, priced with an hourly rate per tag.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::TimerPhase;
+use crate::export::csv::escape_field;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+use crate::report::format_minutes;
+
+const QUARTER_HOUR_MIN: u32 = 15;
+const EXCLUSION_NOTE: &str = "Breaks and skipped or voided pomodoros are not billed.";
+
+/// How worked time is rounded before it is billed.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BillingRounding {
+    /// Every completed pomodoro is rounded up to whole pomodoros of its planned length.
+    #[default]
+    PerPomodoro,
+    /// Every completed pomodoro is rounded up to the next quarter hour.
+    PerQuarterHour,
+    /// The time per tag and day is rounded up to the next quarter hour.
+    PerDay,
+}
+
+impl BillingRounding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BillingRounding::PerPomodoro => "per pomodoro",
+            BillingRounding::PerQuarterHour => "per 15 min",
+            BillingRounding::PerDay => "per day",
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "pomodoro" => Ok(BillingRounding::PerPomodoro),
+            "15min" => Ok(BillingRounding::PerQuarterHour),
+            "day" => Ok(BillingRounding::PerDay),
+            _ => Err(format!("Unknown rounding '{text}', expected pomodoro, 15min or day")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct HourlyRate {
+    pub tag: String,
+    pub rate: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BillingConfig {
+    pub rates: Vec<HourlyRate>,
+    pub rounding: BillingRounding,
+    /// Shown next to amounts, e.g. `EUR`.
+    pub currency: String,
+}
+
+impl BillingConfig {
+    fn rate(&self, tag: &str) -> Option<f64> {
+        let tag = tag.trim();
+        self.rates.iter().find(|rate| rate.tag.trim() == tag).map(|rate| rate.rate)
+    }
+}
+
+/// Billed time for one tag on one day. Billed time is in whole minutes and amounts in whole cents,
+/// so that totals over many lines add up exactly.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BillingLine {
+    pub date: NaiveDate,
+    pub tag: String,
+    pub pomodoros: usize,
+    pub worked_min: f64,
+    pub billed_min: u32,
+    pub rate: f64,
+}
+
+impl BillingLine {
+    /// Returns the amount in cents, rounded to the nearest cent.
+    pub fn amount_cents(&self) -> i64 {
+        let rate_cents = (self.rate * 100.0).round() as i64;
+        (self.billed_min as i64 * rate_cents + 30).div_euclid(60)
+    }
+}
+
+/// Groups the completed pomodoros within `range` by day and tag. Only tags with an hourly rate are billed.
+pub fn billing_lines(records: &[PhaseRecord], range: &DateRange, config: &BillingConfig) -> Vec<BillingLine> {
+    let mut lines: BTreeMap<(NaiveDate, String), BillingLine> = BTreeMap::new();
+    let billable = records.iter().filter(|record| {
+        record.phase == TimerPhase::Pomodoro
+            && record.outcome == PhaseOutcome::Completed
+            && range.contains_record(record)
+    });
+    for record in billable {
+        let Some(rate) = config.rate(&record.tag) else {
+            continue;
+        };
+        let tag = record.tag.trim().to_string();
+        let line = lines.entry((record.local_date(), tag.clone())).or_insert_with(|| BillingLine {
+            date: record.local_date(),
+            tag,
+            pomodoros: 0,
+            worked_min: 0.0,
+            billed_min: 0,
+            rate,
+        });
+        line.pomodoros += 1;
+        line.worked_min += record.actual_min as f64;
+        line.billed_min += match config.rounding {
+            BillingRounding::PerPomodoro => round_up(record.actual_min as f64, record.planned_min.round() as u32),
+            BillingRounding::PerQuarterHour => round_up(record.actual_min as f64, QUARTER_HOUR_MIN),
+            BillingRounding::PerDay => 0,
+        };
+    }
+
+    let mut lines: Vec<BillingLine> = lines.into_values().collect();
+    if config.rounding == BillingRounding::PerDay {
+        for line in &mut lines {
+            line.billed_min = round_up(line.worked_min, QUARTER_HOUR_MIN);
+        }
+    }
+    lines
+}
+
+/// Rounds `minutes` up to a whole number of `unit` minutes.
+fn round_up(minutes: f64, unit: u32) -> u32 {
+    // Rounding the minutes first keeps a pomodoro of 15.0001 minutes from being billed as 30.
+    let minutes = minutes.round().max(0.0) as u32;
+    let unit = unit.max(1);
+    minutes.div_ceil(unit) * unit
+}
+
+fn format_hours(minutes: f64) -> String {
+    format!("{:.2}", minutes / 60.0)
+}
+
+fn format_amount(cents: i64, currency: &str) -> String {
+    let amount = format!("{}{}.{:02}", if cents < 0 { "-" } else { "" }, cents.abs() / 100, cents.abs() % 100);
+    if currency.is_empty() { amount } else { format!("{amount} {currency}") }
+}
+
+fn format_rate(rate: f64, currency: &str) -> String {
+    format_amount((rate * 100.0).round() as i64, currency)
+}
+
+/// Writes the billing lines as CSV with hours as decimals.
+pub fn write_billing_csv<W: Write>(writer: &mut W, lines: &[BillingLine]) -> io::Result<()> {
+    write!(writer, "date,tag,pomodoros,worked_hours,billed_hours,rate,amount\r\n")?;
+    for line in lines {
+        write!(
+            writer,
+            "{},{},{},{},{},{},{}\r\n",
+            line.date,
+            escape_field(&line.tag),
+            line.pomodoros,
+            format_hours(line.worked_min),
+            format_hours(line.billed_min as f64),
+            format_rate(line.rate, ""),
+            format_amount(line.amount_cents(), "")
+        )?;
+    }
+    Ok(())
+}
+
+/// Builds a Markdown billing report with one row per tag and day and a total per tag.
+pub fn billing_markdown(lines: &[BillingLine], range: &DateRange, config: &BillingConfig) -> String {
+    let period = match (range.from, range.to) {
+        (Some(from), Some(to)) => format!("{from} to {to}"),
+        (Some(from), None) => format!("since {from}"),
+        (None, Some(to)) => format!("until {to}"),
+        (None, None) => "all time".to_string(),
+    };
+    let mut report = vec![
+        format!("# Billable hours, {period}"),
+        String::new(),
+        format!("Rounded {}. {EXCLUSION_NOTE}", config.rounding.name()),
+        String::new(),
+        "| Date | Tag | Pomodoros | Worked | Billed | Rate | Amount |".to_string(),
+        "| --- | --- | ---: | ---: | ---: | ---: | ---: |".to_string(),
+    ];
+    let mut totals: BTreeMap<&str, (u32, i64)> = BTreeMap::new();
+    for line in lines {
+        report.push(format!(
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            line.date,
+            line.tag.replace('|', "\\|"),
+            line.pomodoros,
+            format_minutes(line.worked_min as f32),
+            format_minutes(line.billed_min as f32),
+            format_rate(line.rate, &config.currency),
+            format_amount(line.amount_cents(), &config.currency)
+        ));
+        let total = totals.entry(&line.tag).or_default();
+        total.0 += line.billed_min;
+        total.1 += line.amount_cents();
+    }
+
+    report.extend([String::new(), "## Totals".to_string(), String::new()]);
+    for (tag, (billed_min, amount)) in &totals {
+        report.push(format!(
+            "- {tag}: {} h, {}",
+            format_hours(*billed_min as f64),
+            format_amount(*amount, &config.currency)
+        ));
+    }
+    let amount: i64 = totals.values().map(|(_, amount)| amount).sum();
+    report.push(format!("- **Total: {}**", format_amount(amount, &config.currency)));
+
+    let mut markdown = report.join("\n");
+    markdown.push('\n');
+    markdown
+}
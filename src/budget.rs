@@ -0,0 +1,103 @@
+//! Weekly or monthly focus-time budgets per tag.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::TimerPhase;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+use crate::report::{ReportPeriod, format_minutes};
+
+/// Share of a budget at which a first warning is shown.
+pub const WARNING_RATIO: f32 = 0.8;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    #[default]
+    Week,
+    Month,
+}
+
+impl BudgetPeriod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Week => "week",
+            BudgetPeriod::Month => "month",
+        }
+    }
+
+    /// Returns the Monday-to-Sunday week or the calendar month containing `date`.
+    pub fn range(&self, date: NaiveDate) -> DateRange {
+        match self {
+            BudgetPeriod::Week => ReportPeriod::Week(date).range(),
+            BudgetPeriod::Month => {
+                let first = date.with_day(1).unwrap_or(date);
+                let next_month = first.checked_add_months(chrono::Months::new(1)).unwrap_or(first);
+                DateRange::new(Some(first), Some(next_month - Duration::days(1)))
+            }
+        }
+    }
+}
+
+/// Focus time allocated to a tag per week or month.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Budget {
+    pub tag: String,
+    pub hours: f32,
+    pub period: BudgetPeriod,
+}
+
+impl Budget {
+    pub fn limit_min(&self) -> f32 {
+        self.hours * 60.0
+    }
+
+    /// Sums the completed pomodoros on the budget's tag within the period containing `date`.
+    pub fn used_min(&self, records: &[PhaseRecord], date: NaiveDate) -> f32 {
+        let range = self.period.range(date);
+        records
+            .iter()
+            .filter(|record| {
+                record.phase == TimerPhase::Pomodoro
+                    && record.outcome == PhaseOutcome::Completed
+                    && record.tag.trim() == self.tag.trim()
+                    && range.contains_record(record)
+            })
+            .map(|record| record.actual_min)
+            .sum()
+    }
+
+    /// Formats e.g. `migration: 6 h 30 min of 10 h 00 min this week (65%)`.
+    pub fn progress_text(&self, used_min: f32) -> String {
+        let percent = if self.limit_min() > 0.0 { used_min / self.limit_min() * 100.0 } else { 100.0 };
+        format!(
+            "{}: {} of {} this {} ({percent:.0}%)",
+            self.tag,
+            format_minutes(used_min),
+            format_minutes(self.limit_min()),
+            self.period.name()
+        )
+    }
+}
+
+/// Returns the budget for `tag`, if any.
+pub fn find<'a>(budgets: &'a [Budget], tag: &str) -> Option<&'a Budget> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return None;
+    }
+    budgets.iter().find(|budget| budget.tag.trim() == tag)
+}
+
+/// Returns the warning to show when usage grows from `before_min` to `after_min`,
+/// if that crosses 80% or 100% of the budget.
+pub fn threshold_warning(budget: &Budget, before_min: f32, after_min: f32) -> Option<String> {
+    let limit = budget.limit_min();
+    if before_min < limit && after_min >= limit {
+        Some(format!("The {} budget for '{}' is used up", budget.period.name(), budget.tag))
+    } else if before_min < limit * WARNING_RATIO && after_min >= limit * WARNING_RATIO {
+        Some(format!("80% of the {} budget for '{}' is used", budget.period.name(), budget.tag))
+    } else {
+        None
+    }
+}
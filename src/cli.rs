@@ -0,0 +1,373 @@
+//! Command line handling for the non-interactive modes of the application.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use chrono::{Duration, Local};
+
+use crate::billing::{self, BillingRounding};
+use crate::commits;
+use crate::config::Config;
+use crate::export::org::OrgGrouping;
+use crate::export::timewarrior;
+use crate::export::{ExportFormat, ExportOptions};
+use crate::history::{DateRange, History, parse_date};
+use crate::import::{self, ColumnMapping, ImportPreset};
+use crate::report::{self, ReportPeriod};
+use crate::stats::{Stats, StatsGrouping};
+
+const DEFAULT_IMPORT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub const USAGE: &str = "\
+Usage: mypomodoro [OPTIONS]
+       mypomodoro report [REPORT OPTIONS]
+       mypomodoro stats [STATS OPTIONS]
+       mypomodoro billing [BILLING OPTIONS]
+       mypomodoro import <FILE> [IMPORT OPTIONS]
+
+Without options the timer window is opened.
+
+Options:
+  --export-csv <FILE>   Export recorded phases as CSV (use - for stdout)
+  --export-ics <FILE>   Export completed phases as iCalendar events (use - for stdout)
+  --merge               Merge consecutive pomodoros on the same task into one event
+  --export-org <FILE>   Append completed pomodoros as Org CLOCK entries (use - for stdout)
+  --group-by <task|tag> Heading that Org CLOCK entries are filed under (default: task)
+  --export-timew        Add completed pomodoros to the Timewarrior database
+  --timew-dir <DIR>     Timewarrior database directory (default: as found by timew)
+  --from <YYYY-MM-DD>   Only export phases started on or after this date
+  --to <YYYY-MM-DD>     Only export phases started on or before this date
+  -h, --help            Print this help
+
+Report options (Markdown summary, today by default):
+  --day <YYYY-MM-DD>    Report on a single day
+  --yesterday           Report on yesterday
+  --week <YYYY-MM-DD>   Report on the Monday-to-Sunday week containing the date
+  --output <FILE>       Write the report to a file instead of stdout
+
+Stats options (completed pomodoros, today by default):
+  --today               Count today's pomodoros
+  --week                Count the current Monday-to-Sunday week
+  --from <YYYY-MM-DD>   Count pomodoros started on or after this date
+  --to <YYYY-MM-DD>     Count pomodoros started on or before this date
+  --by <tag|task|day>   Add one row per tag, task or day
+  --json                Print JSON instead of a table
+
+Billing options (tags with an hourly rate in the settings, all time by default):
+  --from <YYYY-MM-DD>   Bill pomodoros started on or after this date
+  --to <YYYY-MM-DD>     Bill pomodoros started on or before this date
+  --rounding <RULE>     Round per pomodoro, 15min or day instead of the configured rule
+  --csv                 Print CSV instead of Markdown
+  --output <FILE>       Write the report to a file instead of stdout
+
+Import options (CSV exports of other apps, times in local time):
+  --preset <NAME>       Column layout of toggl or clockify
+  --start <COLUMNS>     Start time column, or columns joined with +, e.g. \"Start date+Start time\"
+  --end <COLUMNS>       End time column or columns
+  --duration <COLUMN>   Duration column in minutes or H:MM:SS, used without --end
+  --label <COLUMN>      Column imported as the task
+  --tag <COLUMN>        Column imported as the tag
+  --time-format <FMT>   strftime format of the start and end (default: %Y-%m-%d %H:%M:%S)
+  --dry-run             Only show what would be imported";
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    /// Open the timer window.
+    Gui,
+    Help,
+    Export {
+        format: ExportFormat,
+        output: PathBuf,
+        range: DateRange,
+        options: ExportOptions,
+    },
+    ExportTimewarrior {
+        database_dir: Option<PathBuf>,
+        range: DateRange,
+    },
+    Report {
+        period: ReportPeriod,
+        output: Option<PathBuf>,
+    },
+    Stats {
+        range: DateRange,
+        grouping: Option<StatsGrouping>,
+        json: bool,
+    },
+    Import {
+        input: PathBuf,
+        mapping: ColumnMapping,
+        dry_run: bool,
+    },
+    Billing {
+        range: DateRange,
+        rounding: Option<BillingRounding>,
+        csv: bool,
+        output: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Returns whether the command adds to the history, so that it needs this machine's journal.
+    pub fn writes_history(&self) -> bool {
+        matches!(self, Command::Gui | Command::Import { dry_run: false, .. })
+    }
+}
+
+/// Parses the command line arguments, excluding the program name.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_some_and(|arg| arg == "report") {
+        args.next();
+        return parse_report_args(args);
+    }
+    if args.peek().is_some_and(|arg| arg == "stats") {
+        args.next();
+        return parse_stats_args(args);
+    }
+    if args.peek().is_some_and(|arg| arg == "import") {
+        args.next();
+        return parse_import_args(args);
+    }
+    if args.peek().is_some_and(|arg| arg == "billing") {
+        args.next();
+        return parse_billing_args(args);
+    }
+
+    let mut export = None;
+    let mut range = DateRange::default();
+    let mut options = ExportOptions::default();
+    let mut export_timew = false;
+    let mut timew_dir = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--export-csv" => export = Some((ExportFormat::Csv, PathBuf::from(expect_value(&mut args, &arg)?))),
+            "--export-ics" => export = Some((ExportFormat::ICalendar, PathBuf::from(expect_value(&mut args, &arg)?))),
+            "--merge" => options.merge_consecutive = true,
+            "--export-org" => export = Some((ExportFormat::Org, PathBuf::from(expect_value(&mut args, &arg)?))),
+            "--group-by" => options.org_grouping = OrgGrouping::parse(&expect_value(&mut args, &arg)?)?,
+            "--export-timew" => export_timew = true,
+            "--timew-dir" => timew_dir = Some(PathBuf::from(expect_value(&mut args, &arg)?)),
+            "--from" => range.from = parse_date(&expect_value(&mut args, &arg)?)?,
+            "--to" => range.to = parse_date(&expect_value(&mut args, &arg)?)?,
+            _ => return Err(format!("Unknown argument '{arg}'")),
+        }
+    }
+    let range = range.validate()?;
+
+    if export_timew {
+        return match export {
+            Some(_) => Err("--export-timew cannot be combined with other exports".to_string()),
+            None => Ok(Command::ExportTimewarrior { database_dir: timew_dir, range }),
+        };
+    }
+    if timew_dir.is_some() {
+        return Err("--timew-dir requires --export-timew".to_string());
+    }
+
+    match export {
+        Some((format, output)) => Ok(Command::Export { format, output, range, options }),
+        None if range != DateRange::default() || options != ExportOptions::default() => {
+            Err("--from, --to, --merge and --group-by require an export option".to_string())
+        }
+        None => Ok(Command::Gui),
+    }
+}
+
+fn parse_report_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let today = Local::now().date_naive();
+    let mut period = ReportPeriod::Day(today);
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--day" => period = ReportPeriod::Day(expect_date(&mut args, &arg)?),
+            "--yesterday" => period = ReportPeriod::Day(today - Duration::days(1)),
+            "--week" => period = ReportPeriod::Week(expect_date(&mut args, &arg)?),
+            "--output" | "-o" => output = Some(PathBuf::from(expect_value(&mut args, &arg)?)),
+            _ => return Err(format!("Unknown report argument '{arg}'")),
+        }
+    }
+    Ok(Command::Report { period, output })
+}
+
+fn parse_stats_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let today = Local::now().date_naive();
+    let mut range = None;
+    let mut custom = DateRange::default();
+    let mut grouping = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--today" => range = Some(ReportPeriod::Day(today).range()),
+            "--week" => range = Some(ReportPeriod::Week(today).range()),
+            "--from" => custom.from = Some(expect_date(&mut args, &arg)?),
+            "--to" => custom.to = Some(expect_date(&mut args, &arg)?),
+            "--by" => grouping = Some(StatsGrouping::parse(&expect_value(&mut args, &arg)?)?),
+            "--json" => json = true,
+            _ => return Err(format!("Unknown stats argument '{arg}'")),
+        }
+    }
+
+    let range = match range {
+        Some(_) if custom != DateRange::default() => {
+            return Err("--from and --to cannot be combined with --today or --week".to_string());
+        }
+        Some(range) => range,
+        None if custom.from.zip(custom.to).is_some_and(|(from, to)| from > to) => {
+            return Err("--from must not be after --to".to_string());
+        }
+        None if custom != DateRange::default() => custom,
+        None => ReportPeriod::Day(today).range(),
+    };
+    Ok(Command::Stats { range, grouping, json })
+}
+
+fn parse_billing_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut from = String::new();
+    let mut to = String::new();
+    let mut rounding = None;
+    let mut csv = false;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--from" => from = expect_value(&mut args, &arg)?,
+            "--to" => to = expect_value(&mut args, &arg)?,
+            "--rounding" => rounding = Some(BillingRounding::parse(&expect_value(&mut args, &arg)?)?),
+            "--csv" => csv = true,
+            "--output" | "-o" => output = Some(PathBuf::from(expect_value(&mut args, &arg)?)),
+            _ => return Err(format!("Unknown billing argument '{arg}'")),
+        }
+    }
+    let range = DateRange::parse(&from, &to)?;
+    Ok(Command::Billing { range, rounding, csv, output })
+}
+
+fn parse_import_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut input = None;
+    let mut mapping = ColumnMapping {
+        time_format: DEFAULT_IMPORT_TIME_FORMAT.to_string(),
+        ..Default::default()
+    };
+    let mut dry_run = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--preset" => mapping = ImportPreset::parse(&expect_value(&mut args, &arg)?)?.mapping(),
+            "--start" => mapping.start = ColumnMapping::parse_columns(&expect_value(&mut args, &arg)?),
+            "--end" => mapping.end = ColumnMapping::parse_columns(&expect_value(&mut args, &arg)?),
+            "--duration" => mapping.duration = Some(expect_value(&mut args, &arg)?),
+            "--label" => mapping.label = Some(expect_value(&mut args, &arg)?),
+            "--tag" => mapping.tag = Some(expect_value(&mut args, &arg)?),
+            "--time-format" => mapping.time_format = expect_value(&mut args, &arg)?,
+            "--dry-run" => dry_run = true,
+            _ if !arg.starts_with('-') && input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unknown import argument '{arg}'")),
+        }
+    }
+    let input = input.ok_or("import requires a CSV file")?;
+    Ok(Command::Import { input, mapping, dry_run })
+}
+
+/// Runs a non-GUI command against the given history and settings.
+pub fn run(command: &Command, history: &mut History, config: &Config) -> io::Result<()> {
+    match command {
+        Command::Gui => Ok(()),
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Command::Export { format, output, range, options } => {
+            if output.as_os_str() == "-" {
+                let mut stdout = io::stdout().lock();
+                format.write(&mut stdout, history.records(), range, options)?;
+                stdout.flush()
+            } else {
+                format.export_to_file(output, history.records(), range, options)
+            }
+        }
+        Command::ExportTimewarrior { database_dir, range } => {
+            let database_dir = database_dir
+                .clone()
+                .or_else(timewarrior::default_database_dir)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No Timewarrior database directory found"))?;
+            let added = timewarrior::export_timewarrior(&database_dir, history.records(), range)?;
+            println!("Added {added} intervals to {}", database_dir.display());
+            Ok(())
+        }
+        Command::Report { period, output } => {
+            let (commits, errors) = commits::read_all_commits(
+                &config.git_repositories,
+                period.range().from,
+                config.git_author.as_deref(),
+            );
+            for error in errors {
+                eprintln!("{error}");
+            }
+            let markdown = report::markdown_report_with_commits(history.records(), &commits, period);
+            match output {
+                Some(path) => fs::write(path, markdown),
+                None => io::stdout().lock().write_all(markdown.as_bytes()),
+            }
+        }
+        Command::Stats { range, grouping, json } => {
+            let stats = Stats::collect(history.records(), range, *grouping);
+            let mut stdout = io::stdout().lock();
+            if *json {
+                serde_json::to_writer_pretty(&mut stdout, &stats)?;
+                writeln!(stdout)
+            } else {
+                stdout.write_all(stats.to_table().as_bytes())
+            }
+        }
+        Command::Import { input, mapping, dry_run } => {
+            let text = fs::read_to_string(input)?;
+            let preview = import::preview(&text, mapping, history.records())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            if *dry_run {
+                for record in &preview.records {
+                    println!("import     {}", import::describe(record));
+                }
+                for (line, record) in &preview.duplicates {
+                    println!("duplicate  {} (line {line})", import::describe(record));
+                }
+            } else {
+                import::import(history, &preview)?;
+            }
+            print!("{}", preview.report());
+            Ok(())
+        }
+        Command::Billing { range, rounding, csv, output } => {
+            let mut billing_config = config.billing.clone();
+            billing_config.rounding = rounding.unwrap_or(billing_config.rounding);
+            let lines = billing::billing_lines(history.records(), range, &billing_config);
+            let mut text = Vec::new();
+            if *csv {
+                billing::write_billing_csv(&mut text, &lines)?;
+            } else {
+                text.extend(billing::billing_markdown(&lines, range, &billing_config).into_bytes());
+            }
+            match output {
+                Some(path) => fs::write(path, text),
+                None => io::stdout().lock().write_all(&text),
+            }
+        }
+    }
+}
+
+fn expect_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{flag} requires a value"))
+}
+
+fn expect_date<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<chrono::NaiveDate, String> {
+    parse_date(&expect_value(args, flag)?)?.ok_or_else(|| format!("{flag} requires a date"))
+}
@@ -0,0 +1,193 @@
+//! Commits from local git repositories, matched to the pomodoros they were authored in.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+use crate::history::PhaseRecord;
+
+const FIELD_SEPARATOR: char = '\u{1f}';
+const SHORT_HASH_LEN: usize = 7;
+/// How long loaded commits are shown before they are read again.
+const CACHE_DURATION: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Commit {
+    /// Name of the repository directory.
+    pub repository: String,
+    pub hash: String,
+    /// Author date of the commit.
+    pub time: DateTime<Utc>,
+    pub summary: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(SHORT_HASH_LEN)]
+    }
+}
+
+/// Reads the commits on all branches of the repository at `repository` that were authored at or after
+/// `since` by `author`, an email address. Without an author, the repository's own git identity is used,
+/// so that commits of teammates are left out.
+pub fn read_commits(repository: &Path, since: Option<DateTime<Utc>>, author: Option<&str>) -> io::Result<Vec<Commit>> {
+    let author = match author {
+        Some(author) => author.to_string(),
+        None => author_email(repository)?,
+    };
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(repository)
+        .args(["log", "--all", "--no-merges", "--fixed-strings", "--format=%H%x1f%at%x1f%s"])
+        .arg(format!("--author=<{author}>"));
+    // --since filters on the committer date, which is normally not before the author date.
+    if let Some(since) = since {
+        command.arg(format!("--since={}", since.format("%Y-%m-%d %H:%M:%S +0000")));
+    }
+    let output = command.output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(format!("git log failed in {}: {message}", repository.display())));
+    }
+
+    let name = repository
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| repository.display().to_string());
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| parse_log_line(line, &name))
+        .filter(|commit| since.is_none_or(|since| commit.time >= since))
+        .collect())
+}
+
+/// Returns the email git would author commits with in `repository`.
+fn author_email(repository: &Path) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(["var", "GIT_AUTHOR_IDENT"])
+        .output()?;
+    let ident = String::from_utf8_lossy(&output.stdout);
+    // The identity reads "Name <email> timestamp timezone".
+    ident
+        .split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(email, _)| email.to_string())
+        .filter(|email| output.status.success() && !email.is_empty())
+        .ok_or_else(|| {
+            io::Error::other(format!("No git user.email set in {}, needed to find your commits", repository.display()))
+        })
+}
+
+fn parse_log_line(line: &str, repository: &str) -> Option<Commit> {
+    let mut fields = line.splitn(3, FIELD_SEPARATOR);
+    let hash = fields.next()?.to_string();
+    let time = DateTime::from_timestamp(fields.next()?.parse().ok()?, 0)?;
+    let summary = fields.next().unwrap_or_default().to_string();
+    Some(Commit { repository: repository.to_string(), hash, time, summary })
+}
+
+/// Reads the commits of all `repositories` authored by `author` on or after the local date `since`, sorted by
+/// author date. Repositories that cannot be read are reported in the returned errors instead of failing the rest.
+pub fn read_all_commits(
+    repositories: &[PathBuf],
+    since: Option<NaiveDate>,
+    author: Option<&str>,
+) -> (Vec<Commit>, Vec<String>) {
+    let since = since.and_then(|date| Local.from_local_datetime(&date.and_time(chrono::NaiveTime::MIN)).earliest());
+    let since = since.map(|time| time.with_timezone(&Utc));
+    let mut commits = Vec::new();
+    let mut errors = Vec::new();
+    for repository in repositories {
+        match read_commits(repository, since, author) {
+            Ok(found) => commits.extend(found),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    commits.sort_by_key(|commit| commit.time);
+    (commits, errors)
+}
+
+/// Returns the commits authored between the start and end of `record`.
+pub fn commits_during<'a>(commits: &'a [Commit], record: &PhaseRecord) -> Vec<&'a Commit> {
+    commits
+        .iter()
+        .filter(|commit| commit.time >= record.start && commit.time <= record.end)
+        .collect()
+}
+
+/// Commits read for one start date, and the repositories that could not be read.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct LoadedCommits {
+    pub commits: Vec<Commit>,
+    pub errors: Vec<String>,
+}
+
+/// Reads commits on background threads, so that `git log` on large repositories never blocks the UI,
+/// and keeps them per start date.
+#[derive(Default)]
+pub struct CommitLoader {
+    repositories: Vec<PathBuf>,
+    author: Option<String>,
+    loaded: HashMap<NaiveDate, (Instant, LoadedCommits)>,
+    pending: HashMap<NaiveDate, Receiver<LoadedCommits>>,
+}
+
+impl CommitLoader {
+    /// Sets the repositories and author to read, forgetting commits loaded for others.
+    pub fn configure(&mut self, repositories: &[PathBuf], author: Option<&str>) {
+        if self.repositories != repositories || self.author.as_deref() != author {
+            self.repositories = repositories.to_vec();
+            self.author = author.map(str::to_string);
+            self.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.repositories.is_empty()
+    }
+
+    /// Forgets the loaded commits so that they are read again.
+    pub fn clear(&mut self) {
+        self.loaded.clear();
+        self.pending.clear();
+    }
+
+    /// Returns the commits authored on or after the local date `since`, or `None` until they are first loaded.
+    /// Starts reading them if they were not read yet or were read too long ago.
+    pub fn commits_since(&mut self, since: NaiveDate) -> Option<&LoadedCommits> {
+        self.receive();
+        let fresh = self.loaded.get(&since).is_some_and(|(loaded_at, _)| loaded_at.elapsed() < CACHE_DURATION);
+        if !fresh && !self.pending.contains_key(&since) {
+            let (sender, receiver) = mpsc::channel();
+            let (repositories, author) = (self.repositories.clone(), self.author.clone());
+            thread::spawn(move || {
+                let (commits, errors) = read_all_commits(&repositories, Some(since), author.as_deref());
+                // The loader may have been cleared in the meantime.
+                let _ = sender.send(LoadedCommits { commits, errors });
+            });
+            self.pending.insert(since, receiver);
+        }
+        self.loaded.get(&since).map(|(_, loaded)| loaded)
+    }
+
+    fn receive(&mut self) {
+        let loaded = &mut self.loaded;
+        self.pending.retain(|since, receiver| match receiver.try_recv() {
+            Ok(commits) => {
+                loaded.insert(*since, (Instant::now(), commits));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+    }
+}
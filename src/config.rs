@@ -0,0 +1,78 @@
+//! User settings persisted as JSON in the configuration directory.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::billing::BillingConfig;
+use crate::alarm::AlarmConfig;
+use crate::ambient::AmbientConfig;
+use crate::budget::Budget;
+use crate::quiet::QuietConfig;
+use crate::sound::{SoundEvent, SoundSlot, Volume};
+use crate::tick::TickConfig;
+use crate::warning::WarningConfig;
+
+const APP_DIR_NAME: &str = "mypomodoro";
+const CONFIG_FILE_NAME: &str = "config.json";
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub budgets: Vec<Budget>,
+    pub billing: BillingConfig,
+    /// Local git repositories whose commits are listed next to the pomodoros they were authored in.
+    pub git_repositories: Vec<PathBuf>,
+    /// Email of the author whose commits are listed, or `None` for the git identity of each repository.
+    pub git_author: Option<String>,
+    /// todo.txt file offered in the task picker.
+    pub todo_txt: Option<PathBuf>,
+    /// Taskwarrior `task` program; the integration is off while this is unset.
+    pub taskwarrior_command: Option<PathBuf>,
+    /// Audio file played instead of the built-in bell.
+    pub sound_file: Option<PathBuf>,
+    /// Name of the output device for sounds, or `None` for the system default.
+    pub audio_device: Option<String>,
+    pub volume: Volume,
+    pub ambient: AmbientConfig,
+    pub ticking: TickConfig,
+    pub warning: WarningConfig,
+    pub alarm: AlarmConfig,
+    pub quiet: QuietConfig,
+    /// Sound settings per event; events without an entry use `SoundSlot::default_for`.
+    pub sounds: BTreeMap<SoundEvent, SoundSlot>,
+}
+
+impl Config {
+    /// Returns the sound settings of `event`.
+    pub fn sound_slot(&self, event: SoundEvent) -> SoundSlot {
+        self.sounds.get(&event).cloned().unwrap_or_else(|| SoundSlot::default_for(event))
+    }
+
+    /// Returns `config.json` in the platform configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Loads the configuration, returning the defaults if the file does not exist yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(io::Error::from),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the configuration through a temporary file so a crash never leaves it half written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp_path, path)
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+
+use chrono::Local;
+
+use crate::history::{DateRange, PhaseRecord};
+
+const LOCAL_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const HEADER: [&str; 10] = [
+    "start",
+    "end",
+    "phase",
+    "planned_minutes",
+    "actual_minutes",
+    "pause_minutes",
+    "outcome",
+    "tag",
+    "task",
+    "interruptions",
+];
+
+/// Writes the records within `range` as CSV, with times in the local time zone.
+pub fn write_csv<W: Write>(writer: &mut W, records: &[PhaseRecord], range: &DateRange) -> io::Result<()> {
+    write_row(writer, HEADER.iter().map(|s| s.to_string()))?;
+    for record in records.iter().filter(|record| range.contains_record(record)) {
+        let fields = [
+            record.start.with_timezone(&Local).format(LOCAL_TIME_FORMAT).to_string(),
+            record.end.with_timezone(&Local).format(LOCAL_TIME_FORMAT).to_string(),
+            record.phase.name().to_string(),
+            format!("{:.1}", record.planned_min),
+            format!("{:.1}", record.actual_min),
+            format!("{:.1}", record.pause_min),
+            record.outcome.name().to_string(),
+            record.tag.clone(),
+            record.task.clone(),
+            record.interruptions.to_string(),
+        ];
+        write_row(writer, fields.into_iter())?;
+    }
+    Ok(())
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break, doubling inner quotes.
+pub fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_row<W: Write>(writer: &mut W, fields: impl Iterator<Item = String>) -> io::Result<()> {
+    let line = fields.map(|field| escape_field(&field)).collect::<Vec<_>>().join(",");
+    // RFC 4180 uses CRLF line endings, which spreadsheet applications expect.
+    write!(writer, "{line}\r\n")
+}
@@ -0,0 +1,165 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+
+use crate::TimerPhase;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+const PRODUCT_ID: &str = "-//kcrt//MyPomodoro//EN";
+const UID_DOMAIN: &str = "mypomodoro";
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// RFC 5545 limits content lines to 75 octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+/// Longest gap between two pomodoros that still counts as consecutive when merging.
+const MERGE_MAX_GAP_MIN: i64 = 30;
+
+/// A calendar event covering one or more recorded phases.
+struct Event<'a> {
+    first: &'a PhaseRecord,
+    end: DateTime<Utc>,
+    pomodoros: usize,
+}
+
+impl Event<'_> {
+    fn summary(&self) -> String {
+        let name = if self.pomodoros > 1 {
+            format!("{} Pomodoros", self.pomodoros)
+        } else {
+            self.first.phase.name().to_string()
+        };
+        if self.first.tag.is_empty() {
+            name
+        } else {
+            format!("{name} [{}]", self.first.tag)
+        }
+    }
+}
+
+/// Writes the completed phases within `range` as an iCalendar file.
+/// With `merge` set, consecutive pomodoros on the same task become a single event
+/// that also spans the breaks between them.
+pub fn write_ics<W: Write>(writer: &mut W, records: &[PhaseRecord], range: &DateRange, merge: bool) -> io::Result<()> {
+    let mut completed: Vec<&PhaseRecord> = records
+        .iter()
+        .filter(|record| record.outcome == PhaseOutcome::Completed && range.contains_record(record))
+        .collect();
+    completed.sort_by_key(|record| record.start);
+
+    let events = if merge { merged_events(&completed) } else { single_events(&completed) };
+
+    write_line(writer, "BEGIN:VCALENDAR")?;
+    write_line(writer, "VERSION:2.0")?;
+    write_line(writer, &format!("PRODID:{PRODUCT_ID}"))?;
+    write_line(writer, "CALSCALE:GREGORIAN")?;
+    for event in &events {
+        write_event(writer, event)?;
+    }
+    write_line(writer, "END:VCALENDAR")
+}
+
+/// Returns a UID derived from the start of the phase, so re-exports update existing events.
+pub fn event_uid(record: &PhaseRecord) -> String {
+    let phase = match record.phase {
+        TimerPhase::Pomodoro => "pomodoro",
+        TimerPhase::ShortBreak => "short-break",
+        TimerPhase::LongBreak => "long-break",
+    };
+    format!("{}-{phase}@{UID_DOMAIN}", record.start.format(UTC_FORMAT))
+}
+
+/// Escapes TEXT property values as described in RFC 5545 section 3.3.11.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a content line into folded lines of at most 75 octets, keeping UTF-8 characters intact.
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space of a continuation line counts towards its length.
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded
+}
+
+fn single_events<'a>(records: &[&'a PhaseRecord]) -> Vec<Event<'a>> {
+    records
+        .iter()
+        .map(|record| Event {
+            first: record,
+            end: record.end,
+            pomodoros: usize::from(record.phase == TimerPhase::Pomodoro),
+        })
+        .collect()
+}
+
+fn merged_events<'a>(records: &[&'a PhaseRecord]) -> Vec<Event<'a>> {
+    let mut events: Vec<Event<'a>> = Vec::new();
+    // Breaks seen since the last pomodoro; they are absorbed if the next pomodoro joins the group.
+    let mut pending_breaks: Vec<&'a PhaseRecord> = Vec::new();
+
+    for &record in records {
+        if record.phase != TimerPhase::Pomodoro {
+            pending_breaks.push(record);
+            continue;
+        }
+        if let Some(last) = events.last_mut()
+            && last.pomodoros > 0
+            && last.first.task == record.task
+            && last.first.tag == record.tag
+            && record.start.signed_duration_since(last.end).num_minutes() <= MERGE_MAX_GAP_MIN
+        {
+            last.end = record.end;
+            last.pomodoros += 1;
+            pending_breaks.clear();
+            continue;
+        }
+        events.extend(single_events(&pending_breaks));
+        pending_breaks.clear();
+        events.push(Event { first: record, end: record.end, pomodoros: 1 });
+    }
+    events.extend(single_events(&pending_breaks));
+    events
+}
+
+fn write_event<W: Write>(writer: &mut W, event: &Event) -> io::Result<()> {
+    write_line(writer, "BEGIN:VEVENT")?;
+    write_line(writer, &format!("UID:{}", event_uid(event.first)))?;
+    // The end of the phase is used as the timestamp so repeated exports produce identical files.
+    write_line(writer, &format!("DTSTAMP:{}", event.end.format(UTC_FORMAT)))?;
+    write_line(writer, &format!("DTSTART:{}", event.first.start.format(UTC_FORMAT)))?;
+    write_line(writer, &format!("DTEND:{}", event.end.format(UTC_FORMAT)))?;
+    write_line(writer, &format!("SUMMARY:{}", escape_text(&event.summary())))?;
+    let description: Vec<&str> = [event.first.task.as_str(), event.first.note.as_str()]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect();
+    if !description.is_empty() {
+        write_line(writer, &format!("DESCRIPTION:{}", escape_text(&description.join("\n\n"))))?;
+    }
+    if !event.first.tag.is_empty() {
+        write_line(writer, &format!("CATEGORIES:{}", escape_text(&event.first.tag)))?;
+    }
+    write_line(writer, "END:VEVENT")
+}
+
+fn write_line<W: Write>(writer: &mut W, line: &str) -> io::Result<()> {
+    write!(writer, "{}\r\n", fold_line(line))
+}
@@ -0,0 +1,81 @@
+//! Exporters that turn the recorded phase history into other file formats.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::history::{DateRange, PhaseRecord};
+
+pub mod csv;
+pub mod ical;
+pub mod org;
+pub mod timewarrior;
+
+use org::OrgGrouping;
+
+/// File formats that can be written from the history in one go.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    ICalendar,
+    Org,
+}
+
+/// Format specific settings shared by the command line and the export window.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ExportOptions {
+    /// Merge consecutive pomodoros on the same task into one calendar event.
+    pub merge_consecutive: bool,
+    /// How Org CLOCK entries are filed under headings.
+    pub org_grouping: OrgGrouping,
+}
+
+impl ExportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::ICalendar => "iCalendar",
+            ExportFormat::Org => "Org CLOCK",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::ICalendar => "ics",
+            ExportFormat::Org => "org",
+        }
+    }
+
+    /// Writes the records within `range` in this format.
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        records: &[PhaseRecord],
+        range: &DateRange,
+        options: &ExportOptions,
+    ) -> io::Result<()> {
+        match self {
+            ExportFormat::Csv => csv::write_csv(writer, records, range),
+            ExportFormat::ICalendar => ical::write_ics(writer, records, range, options.merge_consecutive),
+            ExportFormat::Org => org::write_org(writer, records, range, options.org_grouping),
+        }
+    }
+
+    /// Exports the records within `range` to the file at `path`.
+    /// Org files are appended to without duplicating entries; other formats replace the file.
+    pub fn export_to_file(
+        &self,
+        path: &Path,
+        records: &[PhaseRecord],
+        range: &DateRange,
+        options: &ExportOptions,
+    ) -> io::Result<()> {
+        if *self == ExportFormat::Org {
+            return org::append_org(path, records, range, options.org_grouping).map(|_| ());
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, records, range, options)?;
+        writer.flush()
+    }
+}
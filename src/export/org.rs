@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::TimerPhase;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+const ORG_TIME_FORMAT: &str = "%Y-%m-%d %a %H:%M";
+const UNNAMED_HEADING: &str = "Unsorted";
+const LOGBOOK_START: &str = ":LOGBOOK:";
+const DRAWER_END: &str = ":END:";
+const PLANNING_KEYWORDS: [&str; 3] = ["SCHEDULED:", "DEADLINE:", "CLOSED:"];
+
+/// Which record field becomes the Org heading that CLOCK lines are filed under.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OrgGrouping {
+    #[default]
+    Task,
+    Tag,
+}
+
+impl OrgGrouping {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "task" => Ok(OrgGrouping::Task),
+            "tag" => Ok(OrgGrouping::Tag),
+            _ => Err(format!("Unknown grouping '{text}', expected task or tag")),
+        }
+    }
+
+    fn heading(&self, record: &PhaseRecord) -> String {
+        let name = match self {
+            OrgGrouping::Task => record.task.trim(),
+            OrgGrouping::Tag => record.tag.trim(),
+        };
+        let name = if name.is_empty() { UNNAMED_HEADING } else { name };
+        format!("* {name}")
+    }
+}
+
+/// Formats a record the way Org mode's `org-clock-out` writes a clock entry, in the time zone
+/// the record was made in so that the line stays the same when the local time zone changes.
+pub fn clock_line(record: &PhaseRecord) -> String {
+    let (start, end) = record.recorded_times();
+    let minutes = end.signed_duration_since(start).num_minutes().max(0);
+    format!(
+        "CLOCK: [{}]--[{}] => {:2}:{:02}",
+        start.format(ORG_TIME_FORMAT),
+        end.format(ORG_TIME_FORMAT),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Writes the completed pomodoros within `range` as Org headings with CLOCK lines in a `:LOGBOOK:` drawer.
+pub fn write_org<W: Write>(writer: &mut W, records: &[PhaseRecord], range: &DateRange, grouping: OrgGrouping) -> io::Result<()> {
+    for (heading, lines) in grouped_clock_lines(records, range, grouping) {
+        writeln!(writer, "{heading}")?;
+        for line in logbook(lines) {
+            writeln!(writer, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the completed pomodoros within `range` to the Org file at `path`.
+/// Entries already present in the file are left out, so the same range can be appended repeatedly.
+/// New entries go at the top of the `:LOGBOOK:` drawer of an existing heading of the same name,
+/// which is created after its planning line and other drawers if missing, or under a new heading
+/// at the end of the file. Returns the number of entries added.
+pub fn append_org(path: &Path, records: &[PhaseRecord], range: &DateRange, grouping: OrgGrouping) -> io::Result<usize> {
+    let content = if path.exists() { fs::read_to_string(path)? } else { String::new() };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let existing: HashSet<String> = lines.iter().map(|line| line.trim().to_string()).collect();
+
+    let mut added = 0;
+    for (heading, clock_lines) in grouped_clock_lines(records, range, grouping) {
+        let new_lines: Vec<String> = clock_lines.into_iter().filter(|line| !existing.contains(line)).collect();
+        if new_lines.is_empty() {
+            continue;
+        }
+        added += new_lines.len();
+        match lines.iter().position(|line| line.trim_end() == heading) {
+            Some(index) => match find_logbook(&lines, index) {
+                Ok(start) => {
+                    lines.splice(start..start, new_lines);
+                }
+                Err(insert_at) => {
+                    lines.splice(insert_at..insert_at, logbook(new_lines));
+                }
+            },
+            None => {
+                lines.push(heading);
+                lines.extend(logbook(new_lines));
+            }
+        }
+    }
+
+    if added > 0 {
+        let mut output = lines.join("\n");
+        output.push('\n');
+        fs::write(path, output)?;
+    }
+    Ok(added)
+}
+
+/// Looks for the `:LOGBOOK:` drawer of the heading at `heading` and returns the index of its
+/// first entry, or, if there is none, `Err` with the index after the heading's planning line and drawers.
+fn find_logbook(lines: &[String], heading: usize) -> Result<usize, usize> {
+    let mut index = heading + 1;
+    if lines.get(index).is_some_and(|line| PLANNING_KEYWORDS.iter().any(|keyword| line.trim_start().starts_with(keyword))) {
+        index += 1;
+    }
+    while let Some(line) = lines.get(index).map(|line| line.trim()) {
+        if line == LOGBOOK_START {
+            return Ok(index + 1);
+        }
+        if !is_drawer_start(line) {
+            break;
+        }
+        match lines[index + 1..].iter().position(|line| line.trim() == DRAWER_END) {
+            Some(offset) => index += offset + 2,
+            None => break,
+        }
+    }
+    Err(index)
+}
+
+/// Returns whether `line` opens a drawer such as `:PROPERTIES:`.
+fn is_drawer_start(line: &str) -> bool {
+    line.len() > 2
+        && line != DRAWER_END
+        && line.starts_with(':')
+        && line.ends_with(':')
+        && line[1..line.len() - 1].chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Wraps clock lines in a `:LOGBOOK:` drawer.
+fn logbook(lines: Vec<String>) -> Vec<String> {
+    let mut drawer = Vec::with_capacity(lines.len() + 2);
+    drawer.push(LOGBOOK_START.to_string());
+    drawer.extend(lines);
+    drawer.push(DRAWER_END.to_string());
+    drawer
+}
+
+/// Groups clock lines by heading, newest entry first as Org mode itself inserts them.
+fn grouped_clock_lines(records: &[PhaseRecord], range: &DateRange, grouping: OrgGrouping) -> BTreeMap<String, Vec<String>> {
+    let mut pomodoros: Vec<&PhaseRecord> = records
+        .iter()
+        .filter(|record| {
+            record.phase == TimerPhase::Pomodoro
+                && record.outcome == PhaseOutcome::Completed
+                && range.contains_record(record)
+        })
+        .collect();
+    pomodoros.sort_by_key(|record| std::cmp::Reverse(record.start));
+
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for record in pomodoros {
+        groups.entry(grouping.heading(record)).or_default().push(clock_line(record));
+    }
+    groups
+}
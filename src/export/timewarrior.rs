@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::TimerPhase;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+const DATA_SUBDIR: &str = "data";
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const MONTH_FILE_FORMAT: &str = "%Y-%m.data";
+
+/// Locates the Timewarrior database the same way `timew` does:
+/// `$TIMEWARRIORDB`, then `~/.timewarrior`, then the XDG data directory.
+pub fn default_database_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("TIMEWARRIORDB") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(legacy) = dirs::home_dir().map(|home| home.join(".timewarrior"))
+        && legacy.is_dir()
+    {
+        return Some(legacy);
+    }
+    dirs::data_dir().map(|dir| dir.join("timewarrior"))
+}
+
+/// Formats a record as a Timewarrior interval line, e.g.
+/// `inc 20261018T090000Z - 20261018T092500Z # "deep work"`. Only the pomodoro tag becomes a
+/// Timewarrior tag; free-text task titles would clutter `timew` tag reports.
+pub fn interval_line(record: &PhaseRecord) -> String {
+    let mut line = format!(
+        "inc {} - {}",
+        record.start.format(UTC_FORMAT),
+        record.end.format(UTC_FORMAT)
+    );
+    let tag = record.tag.trim();
+    if !tag.is_empty() {
+        line.push_str(" # ");
+        line.push_str(&quote_tag(tag));
+    }
+    line
+}
+
+/// Writes the completed pomodoros within `range` into the monthly data files of the
+/// Timewarrior database at `database_dir`. Intervals already present are not written again, and
+/// each month file is rewritten with its existing and new lines in chronological order, as
+/// Timewarrior expects. Returns the number of intervals added.
+pub fn export_timewarrior(database_dir: &Path, records: &[PhaseRecord], range: &DateRange) -> io::Result<usize> {
+    let mut months: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for record in records {
+        if record.phase == TimerPhase::Pomodoro
+            && record.outcome == PhaseOutcome::Completed
+            && range.contains_record(record)
+        {
+            months
+                .entry(record.start.format(MONTH_FILE_FORMAT).to_string())
+                .or_default()
+                .push(interval_line(record));
+        }
+    }
+
+    let data_dir = database_dir.join(DATA_SUBDIR);
+    fs::create_dir_all(&data_dir)?;
+
+    let mut added = 0;
+    for (file_name, mut new_lines) in months {
+        let path = data_dir.join(file_name);
+        let existing = if path.exists() { fs::read_to_string(&path)? } else { String::new() };
+        let mut seen: HashSet<&str> = existing.lines().collect();
+        new_lines.sort();
+        let missing: Vec<&str> = new_lines.iter().map(String::as_str).filter(|line| seen.insert(line)).collect();
+        if missing.is_empty() {
+            continue;
+        }
+        // Lines start with "inc <start>", so sorting them orders the intervals chronologically.
+        let mut lines: Vec<&str> = existing.lines().filter(|line| !line.trim().is_empty()).collect();
+        lines.extend(&missing);
+        lines.sort();
+        let mut content = lines.join("\n");
+        content.push('\n');
+        // Written next to the month file and renamed over it, so that an interrupted export
+        // never leaves Timewarrior with a partial file.
+        let temp_path = path.with_extension("data.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &path)?;
+        added += missing.len();
+    }
+    Ok(added)
+}
+
+/// Quotes tags containing whitespace or quotes, as Timewarrior does.
+fn quote_tag(tag: &str) -> String {
+    if tag.contains(char::is_whitespace) || tag.contains('"') {
+        format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        tag.to_string()
+    }
+}
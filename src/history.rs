@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::TimerPhase;
+
+const APP_DIR_NAME: &str = "mypomodoro";
+const JOURNAL_DIR_NAME: &str = "history";
+const JOURNAL_PREFIX: &str = "journal-";
+const JOURNAL_EXTENSION: &str = "jsonl";
+const MACHINE_ID_FILE_NAME: &str = "machine-id";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How a recorded phase came to an end.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum PhaseOutcome {
+    /// The timer ran down to zero.
+    #[default]
+    Completed,
+    /// The user skipped the rest of the phase.
+    Skipped,
+    /// The timer was reset before the phase finished.
+    Voided,
+}
+
+impl PhaseOutcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhaseOutcome::Completed => "completed",
+            PhaseOutcome::Skipped => "skipped",
+            PhaseOutcome::Voided => "voided",
+        }
+    }
+}
+
+/// A single finished phase as stored in the history journals.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct PhaseRecord {
+    /// Stable identifier shared by every version of the record across machines.
+    /// Assigned by [`History::append`] when left empty.
+    #[serde(default)]
+    pub id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub phase: TimerPhase,
+    pub planned_min: f32,
+    pub actual_min: f32,
+    pub pause_min: f32,
+    pub outcome: PhaseOutcome,
+    #[serde(default)]
+    pub tag: String,
+    #[serde(default)]
+    pub task: String,
+    #[serde(default)]
+    pub interruptions: u32,
+    /// What the user planned to do, entered when the pomodoro started.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub intention: String,
+    /// Self-assessed focus from 1 to 5, entered when the pomodoro ended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_rating: Option<u8>,
+    /// Free-text note entered when the phase ended.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// UUID of the Taskwarrior task the phase was worked on, if it was picked from Taskwarrior.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub taskwarrior_uuid: String,
+    /// Seconds it took to acknowledge the alarm at the end of the phase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm_acknowledged_s: Option<f32>,
+    /// Whether the alarm at the end of the phase was never acknowledged, because the next phase ended first.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub alarm_missed: bool,
+    /// Offset of the local time zone from UTC when the phase started, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_s: Option<i32>,
+}
+
+impl PhaseRecord {
+    /// Returns the local calendar date the phase started on.
+    pub fn local_date(&self) -> NaiveDate {
+        self.start.with_timezone(&Local).date_naive()
+    }
+
+    /// Returns the start and end in the time zone the phase was recorded in, so that exports
+    /// keep the wall-clock times after the local time zone changes. Records without an offset
+    /// fall back to the current local time zone.
+    pub fn recorded_times(&self) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        let offset = self
+            .utc_offset_s
+            .and_then(FixedOffset::east_opt)
+            .unwrap_or_else(|| Local.offset_from_utc_datetime(&self.start.naive_utc()).fix());
+        (self.start.with_timezone(&offset), self.end.with_timezone(&offset))
+    }
+
+    /// Returns whether the two phases share any moment in time.
+    pub fn overlaps(&self, other: &PhaseRecord) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// Returns the offset of the local time zone from UTC at `time`, in seconds.
+pub fn local_offset_s(time: DateTime<Utc>) -> i32 {
+    Local.offset_from_utc_datetime(&time.naive_utc()).fix().local_minus_utc()
+}
+
+/// Why a change to the history was refused.
+#[derive(Debug)]
+pub enum HistoryError {
+    /// The record itself is inconsistent or clashes with another record.
+    Invalid(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Invalid(reason) => write!(f, "{reason}"),
+            HistoryError::Io(err) => write!(f, "Failed to save history: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<io::Error> for HistoryError {
+    fn from(err: io::Error) -> Self {
+        HistoryError::Io(err)
+    }
+}
+
+/// Inclusive range of local dates. An open end means "no limit".
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        Self { from, to }
+    }
+
+    /// Parses a range from two `YYYY-MM-DD` strings. Empty strings leave that end open.
+    pub fn parse(from: &str, to: &str) -> Result<Self, String> {
+        Self::new(parse_date(from)?, parse_date(to)?).validate()
+    }
+
+    /// Returns the range unchanged, or an error if it starts after it ends.
+    pub fn validate(self) -> Result<Self, String> {
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from > to
+        {
+            return Err(format!("Start date {from} is after end date {to}"));
+        }
+        Ok(self)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+
+    pub fn contains_record(&self, record: &PhaseRecord) -> bool {
+        self.contains(record.local_date())
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date, treating an empty string as no date.
+pub fn parse_date(text: &str) -> Result<Option<NaiveDate>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text, DATE_FORMAT)
+        .map(Some)
+        .map_err(|_| format!("Invalid date '{text}', expected YYYY-MM-DD"))
+}
+
+/// Returns the directory where the application keeps its data files.
+pub fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME))
+}
+
+fn machine_id_path() -> io::Result<PathBuf> {
+    dirs::config_local_dir()
+        .map(|dir| dir.join(APP_DIR_NAME).join(MACHINE_ID_FILE_NAME))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No configuration directory available"))
+}
+
+/// Returns the identifier of this machine, or `None` if it was never created.
+pub fn existing_machine_id() -> Option<String> {
+    let id = fs::read_to_string(machine_id_path().ok()?).ok()?;
+    Some(id.trim().to_string()).filter(|id| !id.is_empty())
+}
+
+/// Returns the identifier of this machine, creating it on first use.
+/// It lives in the local configuration directory so that it is never synced to other machines.
+pub fn local_machine_id() -> io::Result<String> {
+    if let Some(id) = existing_machine_id() {
+        return Ok(id);
+    }
+    let path = machine_id_path()?;
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, &id)?;
+    Ok(id)
+}
+
+/// One line of a machine's journal. Journals are append-only; the newest entry for a record
+/// id across all journals decides its current state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum JournalEntry {
+    Put {
+        at: DateTime<Utc>,
+        record: PhaseRecord,
+    },
+    Delete {
+        at: DateTime<Utc>,
+        id: String,
+    },
+}
+
+/// Orders entries for the same record: later write time wins, ties are broken by machine id
+/// and then by position in the journal, so every machine settles on the same result.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Stamp {
+    at: DateTime<Utc>,
+    machine: String,
+    line: usize,
+}
+
+/// Phase history merged from the journals of all machines sharing a directory.
+/// Each machine only ever appends to its own `journal-<machine id>.jsonl`, which keeps the
+/// files free of sync conflicts. Without a directory the history only lives in memory.
+#[derive(Debug, Default)]
+pub struct History {
+    /// Current records sorted by start time.
+    records: Vec<PhaseRecord>,
+    /// Newest entry seen for every record id, including deleted ones.
+    latest: HashMap<String, (Stamp, Option<PhaseRecord>)>,
+    dir: Option<PathBuf>,
+    machine_id: String,
+    own_lines: usize,
+    /// Modification time and size of each journal when it was last read.
+    seen: HashMap<PathBuf, (SystemTime, u64)>,
+    /// Keeps new entries in memory instead of writing them to this machine's journal.
+    read_only: bool,
+}
+
+impl History {
+    /// Opens the journals in the default data directory.
+    pub fn open_default() -> io::Result<Self> {
+        let dir = default_data_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No data directory available"))?;
+        Self::open(dir.join(JOURNAL_DIR_NAME), local_machine_id()?)
+    }
+
+    /// Opens the journals in the default data directory without creating or changing any file,
+    /// for commands that only read the history.
+    pub fn open_default_read_only() -> io::Result<Self> {
+        let dir = default_data_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No data directory available"))?;
+        let mut history = Self::open(dir.join(JOURNAL_DIR_NAME), existing_machine_id().unwrap_or_default())?;
+        history.read_only = true;
+        Ok(history)
+    }
+
+    /// Opens the journals in `dir`, writing new entries as `machine_id`.
+    /// A missing directory yields an empty history.
+    pub fn open(dir: impl Into<PathBuf>, machine_id: impl Into<String>) -> io::Result<Self> {
+        let mut history = Self {
+            dir: Some(dir.into()),
+            machine_id: machine_id.into(),
+            ..Self::default()
+        };
+        history.reload()?;
+        Ok(history)
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    pub fn machine_id(&self) -> &str {
+        &self.machine_id
+    }
+
+    pub fn records(&self) -> &[PhaseRecord] {
+        &self.records
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PhaseRecord> {
+        self.records.iter().find(|record| record.id == id)
+    }
+
+    /// Returns the records whose start date falls within `range`.
+    pub fn records_in(&self, range: &DateRange) -> Vec<&PhaseRecord> {
+        self.records.iter().filter(|record| range.contains_record(record)).collect()
+    }
+
+    /// Adds a record, assigning it a new id if it has none. Returns the record id.
+    pub fn append(&mut self, mut record: PhaseRecord) -> io::Result<String> {
+        if record.id.is_empty() {
+            record.id = uuid::Uuid::new_v4().simple().to_string();
+        }
+        let id = record.id.clone();
+        self.write(JournalEntry::Put { at: Utc::now(), record })?;
+        Ok(id)
+    }
+
+    /// Replaces the record that has the same id as `record`.
+    pub fn update(&mut self, record: PhaseRecord) -> io::Result<()> {
+        if self.get(&record.id).is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No such history record"));
+        }
+        self.write(JournalEntry::Put { at: Utc::now(), record })
+    }
+
+    /// Checks that `record` has a positive duration and does not overlap any other record.
+    /// A record with the same id is the one being edited and is left out of the check.
+    pub fn validate(&self, record: &PhaseRecord) -> Result<(), HistoryError> {
+        if record.end <= record.start {
+            return Err(HistoryError::Invalid("End time must be after start time".to_string()));
+        }
+        let clash = self
+            .records
+            .iter()
+            .find(|other| (record.id.is_empty() || other.id != record.id) && record.overlaps(other));
+        if let Some(other) = clash {
+            let start = other.start.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            return Err(HistoryError::Invalid(format!(
+                "Overlaps the {} started at {start}",
+                other.phase.name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Adds a manually entered record after validating it. Returns the record id.
+    pub fn add_record(&mut self, record: PhaseRecord) -> Result<String, HistoryError> {
+        self.validate(&record)?;
+        Ok(self.append(record)?)
+    }
+
+    /// Stores an edited version of an existing record after validating it.
+    pub fn edit_record(&mut self, record: PhaseRecord) -> Result<(), HistoryError> {
+        self.validate(&record)?;
+        self.update(record)?;
+        Ok(())
+    }
+
+    /// Deletes the record with the given id.
+    pub fn remove_record(&mut self, id: &str) -> io::Result<PhaseRecord> {
+        let record = self
+            .get(id)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No such history record"))?;
+        self.write(JournalEntry::Delete { at: Utc::now(), id: id.to_string() })?;
+        Ok(record)
+    }
+
+    /// Re-reads the journals if any of them changed since they were last read,
+    /// e.g. because a sync tool brought in entries from another machine.
+    /// Returns true if the journals were re-read.
+    pub fn refresh(&mut self) -> io::Result<bool> {
+        if self.dir.is_none() || self.scan_journals()? == self.seen {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn own_journal_path(&self) -> Option<PathBuf> {
+        if self.read_only {
+            return None;
+        }
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{JOURNAL_PREFIX}{}.{JOURNAL_EXTENSION}", self.machine_id)))
+    }
+
+    /// Appends an entry to this machine's journal and applies it to the merged view.
+    fn write(&mut self, entry: JournalEntry) -> io::Result<()> {
+        if let Some(path) = self.own_journal_path() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().create(true).read(true).append(true).open(&path)?;
+            let mut line = serde_json::to_string(&entry)?;
+            line.push('\n');
+            // After a crash the last line may lack its newline; appending to it would garble this entry too.
+            if !ends_with_newline(&mut file)? {
+                line.insert(0, '\n');
+            }
+            file.write_all(line.as_bytes())?;
+            if let Ok(metadata) = file.metadata() {
+                self.seen.insert(path, (metadata.modified()?, metadata.len()));
+            }
+        }
+        let stamp = Stamp {
+            at: entry_time(&entry),
+            machine: self.machine_id.clone(),
+            line: self.own_lines,
+        };
+        self.own_lines += 1;
+        self.apply(stamp, entry);
+        self.rebuild_view();
+        Ok(())
+    }
+
+    /// Merges an entry into `latest` if it is newer than what is known for its record.
+    fn apply(&mut self, stamp: Stamp, entry: JournalEntry) {
+        let (id, record) = match entry {
+            JournalEntry::Put { record, .. } => (record.id.clone(), Some(record)),
+            JournalEntry::Delete { id, .. } => (id, None),
+        };
+        match self.latest.get(&id) {
+            Some((known, _)) if *known >= stamp => {}
+            _ => {
+                self.latest.insert(id, (stamp, record));
+            }
+        }
+    }
+
+    fn rebuild_view(&mut self) {
+        self.records = self.latest.values().filter_map(|(_, record)| record.clone()).collect();
+        self.records.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    /// Reads every journal in the directory from scratch.
+    fn reload(&mut self) -> io::Result<()> {
+        self.latest.clear();
+        self.own_lines = 0;
+        self.seen = self.scan_journals()?;
+        let journals: Vec<PathBuf> = self.seen.keys().cloned().collect();
+        for path in journals {
+            let machine = journal_machine(&path).unwrap_or_default();
+            let entries = read_journal(&path)?;
+            if machine == self.machine_id {
+                self.own_lines = entries.len();
+            }
+            for (line, entry) in entries.into_iter().enumerate() {
+                let stamp = Stamp { at: entry_time(&entry), machine: machine.clone(), line };
+                self.apply(stamp, entry);
+            }
+        }
+        self.rebuild_view();
+        Ok(())
+    }
+
+    /// Lists the journals in the directory with their modification time and size.
+    fn scan_journals(&self) -> io::Result<HashMap<PathBuf, (SystemTime, u64)>> {
+        let mut journals = HashMap::new();
+        let Some(dir) = &self.dir else {
+            return Ok(journals);
+        };
+        if !dir.is_dir() {
+            return Ok(journals);
+        }
+        for dir_entry in fs::read_dir(dir)? {
+            let path = dir_entry?.path();
+            if journal_machine(&path).is_some() {
+                let metadata = fs::metadata(&path)?;
+                journals.insert(path, (metadata.modified()?, metadata.len()));
+            }
+        }
+        Ok(journals)
+    }
+}
+
+fn entry_time(entry: &JournalEntry) -> DateTime<Utc> {
+    match entry {
+        JournalEntry::Put { at, .. } | JournalEntry::Delete { at, .. } => *at,
+    }
+}
+
+/// Returns the machine id encoded in a journal file name, or `None` for other files.
+fn journal_machine(path: &Path) -> Option<String> {
+    if path.extension()? != JOURNAL_EXTENSION {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    stem.strip_prefix(JOURNAL_PREFIX).map(str::to_string)
+}
+
+/// Returns whether `file` is empty or ends in a newline.
+fn ends_with_newline(file: &mut File) -> io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    let mut last = [0];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+fn read_journal(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // A line cut short by a crash or a sync in progress should not make the journal unreadable.
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,319 @@
+//! Window for adding, correcting and deleting history records by hand.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use eframe::egui;
+
+use crate::TimerPhase;
+use crate::commits::{self, Commit, CommitLoader};
+use crate::history::{self, History, PhaseOutcome, PhaseRecord};
+
+const TIME_FORMAT: &str = "%H:%M";
+const EDITOR_SIZE: [f32; 2] = [560.0, 420.0];
+
+/// Text fields of a record being added or edited.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecordForm {
+    pub date: String,
+    pub start: String,
+    /// Date the record ends on, left empty when it ends on the day it starts.
+    pub end_date: String,
+    pub end: String,
+    pub phase: TimerPhase,
+    pub outcome: PhaseOutcome,
+    pub tag: String,
+    pub task: String,
+}
+
+impl RecordForm {
+    /// Returns an empty form for a pomodoro on `date`.
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date: date.to_string(),
+            start: String::new(),
+            end_date: String::new(),
+            end: String::new(),
+            phase: TimerPhase::Pomodoro,
+            outcome: PhaseOutcome::Completed,
+            tag: String::new(),
+            task: String::new(),
+        }
+    }
+
+    pub fn from_record(record: &PhaseRecord) -> Self {
+        let end = record.end.with_timezone(&Local);
+        Self {
+            date: record.local_date().to_string(),
+            start: record.start.with_timezone(&Local).format(TIME_FORMAT).to_string(),
+            end_date: if end.date_naive() == record.local_date() { String::new() } else { end.date_naive().to_string() },
+            end: end.format(TIME_FORMAT).to_string(),
+            phase: record.phase,
+            outcome: record.outcome,
+            tag: record.tag.clone(),
+            task: record.task.clone(),
+        }
+    }
+
+    /// Builds a record from the form. Fields the form does not show, such as pauses and the
+    /// journal, are taken from `base` when editing an existing record, as are the seconds of
+    /// times whose date and `HH:MM` were left unchanged.
+    pub fn to_record(&self, base: Option<&PhaseRecord>) -> Result<PhaseRecord, String> {
+        let date = history::parse_date(&self.date)?.ok_or("A date is required")?;
+        let end_date = history::parse_date(&self.end_date)?.unwrap_or(date);
+        let mut start = local_time(date, &self.start)?;
+        let mut end = local_time(end_date, &self.end)?;
+        if let Some(base) = base {
+            start = keep_seconds(start, base.start);
+            end = keep_seconds(end, base.end);
+        }
+        let duration_min = end.signed_duration_since(start).num_seconds() as f32 / 60.0;
+
+        let mut record = base.cloned().unwrap_or_default();
+        record.pause_min = record.pause_min.min(duration_min.max(0.0));
+        if base.is_none() {
+            record.planned_min = duration_min;
+        }
+        if base.is_none_or(|base| base.start != start) {
+            record.utc_offset_s = Some(history::local_offset_s(start));
+        }
+        record.start = start;
+        record.end = end;
+        record.actual_min = (duration_min - record.pause_min).max(0.0);
+        record.phase = self.phase;
+        record.outcome = self.outcome;
+        record.tag = self.tag.trim().to_string();
+        record.task = self.task.trim().to_string();
+        Ok(record)
+    }
+}
+
+/// Converts a local `HH:MM` time on `date` to UTC.
+fn local_time(date: NaiveDate, text: &str) -> Result<DateTime<Utc>, String> {
+    let time = NaiveTime::parse_from_str(text.trim(), TIME_FORMAT)
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", text.trim()))?;
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("{date} {text} does not exist in the local time zone"))
+}
+
+/// Returns `original` if `edited` is the same local minute, so that saving a form does not
+/// truncate times the user did not change.
+fn keep_seconds(edited: DateTime<Utc>, original: DateTime<Utc>) -> DateTime<Utc> {
+    let minute = original.with_timezone(&Local).with_second(0).and_then(|time| time.with_nanosecond(0));
+    if minute.is_some_and(|minute| minute == edited) { original } else { edited }
+}
+
+/// State of the history editor window.
+pub struct HistoryEditor {
+    pub open: bool,
+    day: String,
+    form: RecordForm,
+    /// Id of the record loaded into the form, or `None` when adding a new one.
+    editing: Option<String>,
+    error: Option<String>,
+}
+
+impl Default for HistoryEditor {
+    fn default() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            open: false,
+            day: today.to_string(),
+            form: RecordForm::new(today),
+            editing: None,
+            error: None,
+        }
+    }
+}
+
+impl HistoryEditor {
+    /// Shows the editor in its own window, listing the commits loaded by `commits` under each record.
+    pub fn show(&mut self, ctx: &egui::Context, history: &mut History, commits: &mut CommitLoader) {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("history_editor"),
+            egui::ViewportBuilder::default()
+                .with_title("History")
+                .with_inner_size(EDITOR_SIZE),
+            |ctx, class| {
+                if class == egui::ViewportClass::Embedded {
+                    let mut open = self.open;
+                    egui::Window::new("History")
+                        .open(&mut open)
+                        .show(ctx, |ui| self.ui(ui, history, commits));
+                    self.open &= open;
+                } else {
+                    egui::CentralPanel::default().show(ctx, |ui| self.ui(ui, history, commits));
+                    if ctx.input(|input| input.viewport().close_requested()) {
+                        self.open = false;
+                    }
+                }
+            },
+        );
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, history: &mut History, loader: &mut CommitLoader) {
+        ui.horizontal(|ui| {
+            ui.label("Day");
+            ui.add(egui::TextEdit::singleline(&mut self.day).desired_width(90.0));
+            if loader.is_enabled() && ui.button("Reload commits").clicked() {
+                loader.clear();
+            }
+        });
+
+        let day = history::parse_date(&self.day).ok().flatten();
+        let enabled = loader.is_enabled();
+        let loaded = day.filter(|_| enabled).and_then(|day| loader.commits_since(day));
+        match loaded {
+            Some(loaded) => {
+                if let Some(error) = loaded.errors.first() {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            }
+            None if enabled && day.is_some() => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading commits…");
+                });
+            }
+            None => {}
+        }
+        let day_commits: &[Commit] = loaded.map_or(&[], |loaded| &loaded.commits);
+        let mut to_delete = None;
+        egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+            egui::Grid::new("history_records").striped(true).show(ui, |ui| {
+                for header in ["Start", "End", "Phase", "Outcome", "Tag", "Task", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for record in history.records() {
+                    if Some(record.local_date()) != day {
+                        continue;
+                    }
+                    ui.label(record.start.with_timezone(&Local).format(TIME_FORMAT).to_string());
+                    ui.label(record.end.with_timezone(&Local).format(TIME_FORMAT).to_string());
+                    ui.label(record.phase.name());
+                    ui.label(record.outcome.name());
+                    ui.label(&record.tag);
+                    ui.label(&record.task);
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Edit").clicked() {
+                            self.form = RecordForm::from_record(record);
+                            self.editing = Some(record.id.clone());
+                            self.error = None;
+                        }
+                        if ui.small_button("Delete").clicked() {
+                            to_delete = Some(record.id.clone());
+                        }
+                    });
+                    ui.end_row();
+                    for commit in commits::commits_during(day_commits, record) {
+                        ui.label("");
+                        ui.monospace(commit.short_hash());
+                        ui.label(&commit.repository);
+                        ui.label(&commit.summary);
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+
+        if let Some(id) = to_delete {
+            match history.remove_record(&id) {
+                Ok(_) => self.reset_form(),
+                Err(err) => self.error = Some(format!("Failed to delete record: {err}")),
+            }
+        }
+
+        ui.separator();
+        ui.strong(if self.editing.is_some() { "Edit record" } else { "Add record" });
+        self.form_ui(ui);
+
+        ui.horizontal(|ui| {
+            let label = if self.editing.is_some() { "Save changes" } else { "Add" };
+            if ui.button(label).clicked() {
+                self.submit(history);
+            }
+            if self.editing.is_some() && ui.button("Cancel").clicked() {
+                self.reset_form();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn form_ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("history_form").show(ui, |ui| {
+            ui.label("Date");
+            ui.add(egui::TextEdit::singleline(&mut self.form.date).desired_width(90.0));
+            ui.label("Start");
+            ui.add(egui::TextEdit::singleline(&mut self.form.start).hint_text("HH:MM").desired_width(50.0));
+            ui.label("End");
+            ui.add(egui::TextEdit::singleline(&mut self.form.end).hint_text("HH:MM").desired_width(50.0));
+            ui.end_row();
+
+            ui.label("End date");
+            ui.add(egui::TextEdit::singleline(&mut self.form.end_date).hint_text("same day").desired_width(90.0));
+            ui.end_row();
+
+            ui.label("Phase");
+            egui::ComboBox::from_id_salt("history_form_phase")
+                .selected_text(self.form.phase.name())
+                .show_ui(ui, |ui| {
+                    for phase in [TimerPhase::Pomodoro, TimerPhase::ShortBreak, TimerPhase::LongBreak] {
+                        ui.selectable_value(&mut self.form.phase, phase, phase.name());
+                    }
+                });
+            ui.label("Outcome");
+            egui::ComboBox::from_id_salt("history_form_outcome")
+                .selected_text(self.form.outcome.name())
+                .show_ui(ui, |ui| {
+                    for outcome in [PhaseOutcome::Completed, PhaseOutcome::Skipped, PhaseOutcome::Voided] {
+                        ui.selectable_value(&mut self.form.outcome, outcome, outcome.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Tag");
+            ui.add(egui::TextEdit::singleline(&mut self.form.tag).desired_width(90.0));
+            ui.label("Task");
+            ui.add(egui::TextEdit::singleline(&mut self.form.task).desired_width(150.0));
+            ui.end_row();
+        });
+    }
+
+    /// Validates the form and stores it through the history.
+    fn submit(&mut self, history: &mut History) {
+        let base = self.editing.as_deref().and_then(|id| history.get(id));
+        if self.editing.is_some() && base.is_none() {
+            self.error = Some("The record was deleted in the meantime".to_string());
+            return;
+        }
+        let record = match self.form.to_record(base) {
+            Ok(record) => record,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        let result = match self.editing {
+            Some(_) => history.edit_record(record),
+            None => history.add_record(record).map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                self.day = self.form.date.clone();
+                self.reset_form();
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    fn reset_form(&mut self) {
+        let date = history::parse_date(&self.day).ok().flatten().unwrap_or_else(|| Local::now().date_naive());
+        self.form = RecordForm::new(date);
+        self.editing = None;
+        self.error = None;
+    }
+}
@@ -0,0 +1,360 @@
+//! Importing pomodoros from the CSV exports of other timer apps.
+
+use std::io;
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+
+use crate::TimerPhase;
+use crate::history::{self, History, PhaseOutcome, PhaseRecord};
+
+/// Separator for several columns that together hold a time, e.g. `Start date+Start time`.
+pub const COLUMN_JOINER: char = '+';
+
+/// Column layouts of known CSV exports.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImportPreset {
+    /// Toggl Track's detailed report.
+    Toggl,
+    /// Clockify's detailed report with US date format.
+    Clockify,
+}
+
+impl ImportPreset {
+    pub const ALL: [ImportPreset; 2] = [ImportPreset::Toggl, ImportPreset::Clockify];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportPreset::Toggl => "toggl",
+            ImportPreset::Clockify => "clockify",
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name() == text)
+            .ok_or_else(|| format!("Unknown preset '{text}', expected toggl or clockify"))
+    }
+
+    pub fn mapping(&self) -> ColumnMapping {
+        let columns = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        match self {
+            ImportPreset::Toggl => ColumnMapping {
+                start: columns(&["Start date", "Start time"]),
+                end: columns(&["End date", "End time"]),
+                duration: None,
+                label: Some("Description".to_string()),
+                tag: Some("Tags".to_string()),
+                time_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            },
+            ImportPreset::Clockify => ColumnMapping {
+                start: columns(&["Start Date", "Start Time"]),
+                end: columns(&["End Date", "End Time"]),
+                duration: None,
+                label: Some("Description".to_string()),
+                tag: Some("Tags".to_string()),
+                time_format: "%m/%d/%Y %I:%M:%S %p".to_string(),
+            },
+        }
+    }
+}
+
+/// Which CSV columns hold the fields of a pomodoro. Times are read in the local time zone.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ColumnMapping {
+    /// Columns joined with a space to form the start time.
+    pub start: Vec<String>,
+    /// Columns joined with a space to form the end time. When empty, `duration` is used instead.
+    pub end: Vec<String>,
+    /// Column with the duration in minutes or as `H:MM:SS`.
+    pub duration: Option<String>,
+    /// Column imported as the task.
+    pub label: Option<String>,
+    pub tag: Option<String>,
+    /// `strftime` format of the joined start and end columns.
+    pub time_format: String,
+}
+
+impl ColumnMapping {
+    /// Parses a list of column names written as `Start date+Start time`.
+    pub fn parse_columns(text: &str) -> Vec<String> {
+        text.split(COLUMN_JOINER)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+}
+
+/// Text fields of the import window.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportForm {
+    pub start: String,
+    pub end: String,
+    pub duration: String,
+    pub label: String,
+    pub tag: String,
+    pub time_format: String,
+}
+
+impl Default for ImportForm {
+    fn default() -> Self {
+        Self::from_mapping(&ImportPreset::Toggl.mapping())
+    }
+}
+
+impl ImportForm {
+    pub fn from_mapping(mapping: &ColumnMapping) -> Self {
+        let joiner = COLUMN_JOINER.to_string();
+        Self {
+            start: mapping.start.join(&joiner),
+            end: mapping.end.join(&joiner),
+            duration: mapping.duration.clone().unwrap_or_default(),
+            label: mapping.label.clone().unwrap_or_default(),
+            tag: mapping.tag.clone().unwrap_or_default(),
+            time_format: mapping.time_format.clone(),
+        }
+    }
+
+    /// Builds the mapping, leaving out columns whose field is empty.
+    pub fn to_mapping(&self) -> ColumnMapping {
+        let optional = |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+        ColumnMapping {
+            start: ColumnMapping::parse_columns(&self.start),
+            end: ColumnMapping::parse_columns(&self.end),
+            duration: optional(&self.duration),
+            label: optional(&self.label),
+            tag: optional(&self.tag),
+            time_format: self.time_format.trim().to_string(),
+        }
+    }
+}
+
+/// A row that could not be imported.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RowError {
+    /// Line number in the file, starting at 1 for the header.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The outcome of reading a CSV file, before anything is written to the history.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ImportPreview {
+    pub records: Vec<PhaseRecord>,
+    /// Rows with the same start and end, within a minute, as a record already in the history or
+    /// earlier in the file, with their line number.
+    pub duplicates: Vec<(usize, PhaseRecord)>,
+    pub failures: Vec<RowError>,
+}
+
+impl ImportPreview {
+    /// Summarizes the preview, e.g. `12 to import, 3 duplicates, 1 failed`, followed by the failed rows.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "{} to import, {} duplicates, {} failed\n",
+            self.records.len(),
+            self.duplicates.len(),
+            self.failures.len()
+        );
+        for failure in &self.failures {
+            report.push_str(&format!("line {}: {}\n", failure.line, failure.message));
+        }
+        report
+    }
+}
+
+/// Reads `text` as CSV with `mapping`, checking each row against the `existing` records.
+/// Fails only when the header lacks a mapped column; problems with single rows end up in the failures.
+pub fn preview(text: &str, mapping: &ColumnMapping, existing: &[PhaseRecord]) -> Result<ImportPreview, String> {
+    let mut rows = parse_csv_lines(text).into_iter();
+    let (_, header) = rows.next().ok_or("The file is empty")?;
+    let column = |name: &String| {
+        header
+            .iter()
+            .position(|field| field.trim() == name)
+            .ok_or_else(|| format!("Column '{name}' not found"))
+    };
+    if mapping.start.is_empty() {
+        return Err("A start column is required".to_string());
+    }
+    if mapping.end.is_empty() && mapping.duration.is_none() {
+        return Err("An end or duration column is required".to_string());
+    }
+    let columns = Columns {
+        start: mapping.start.iter().map(column).collect::<Result<_, _>>()?,
+        end: mapping.end.iter().map(column).collect::<Result<_, _>>()?,
+        duration: mapping.duration.as_ref().map(column).transpose()?,
+        label: mapping.label.as_ref().map(column).transpose()?,
+        tag: mapping.tag.as_ref().map(column).transpose()?,
+    };
+
+    let mut preview = ImportPreview::default();
+    for (line, row) in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        match parse_row(&row, &columns, &mapping.time_format) {
+            Ok(record) => {
+                let duplicate = existing
+                    .iter()
+                    .chain(&preview.records)
+                    .any(|other| is_same_interval(other, &record));
+                if duplicate {
+                    preview.duplicates.push((line, record));
+                } else {
+                    preview.records.push(record);
+                }
+            }
+            Err(message) => preview.failures.push(RowError { line, message }),
+        }
+    }
+    Ok(preview)
+}
+
+/// Returns whether both records start and end within a minute of each other, as happens when a
+/// pomodoro is imported again, possibly from an export that rounds to the minute.
+fn is_same_interval(a: &PhaseRecord, b: &PhaseRecord) -> bool {
+    let close = |x: DateTime<Utc>, y: DateTime<Utc>| (x - y).abs() <= Duration::minutes(1);
+    close(a.start, b.start) && close(a.end, b.end)
+}
+
+/// Positions of the mapped columns in the header.
+struct Columns {
+    start: Vec<usize>,
+    end: Vec<usize>,
+    duration: Option<usize>,
+    label: Option<usize>,
+    tag: Option<usize>,
+}
+
+fn parse_row(row: &[String], columns: &Columns, time_format: &str) -> Result<PhaseRecord, String> {
+    let field = |column: usize| row.get(column).map(|field| field.trim()).unwrap_or_default();
+    let joined = |columns: &[usize]| columns.iter().map(|&column| field(column)).collect::<Vec<_>>().join(" ");
+
+    let start = parse_local_time(&joined(&columns.start), time_format)?;
+    let end = match columns.duration {
+        Some(column) if columns.end.is_empty() => start
+            .checked_add_signed(parse_duration(field(column))?)
+            .ok_or_else(|| format!("Duration '{}' is too long", field(column)))?,
+        _ => parse_local_time(&joined(&columns.end), time_format)?,
+    };
+    if end <= start {
+        return Err("End time is not after start time".to_string());
+    }
+    let minutes = end.signed_duration_since(start).num_seconds() as f32 / 60.0;
+    Ok(PhaseRecord {
+        start,
+        end,
+        phase: TimerPhase::Pomodoro,
+        planned_min: minutes,
+        actual_min: minutes,
+        outcome: PhaseOutcome::Completed,
+        task: columns.label.map(field).unwrap_or_default().to_string(),
+        tag: columns.tag.map(field).unwrap_or_default().to_string(),
+        utc_offset_s: Some(history::local_offset_s(start)),
+        ..Default::default()
+    })
+}
+
+/// Describes a record in a preview, e.g. `2026-10-15 09:00–09:25 Write report [work]`.
+pub fn describe(record: &PhaseRecord) -> String {
+    let mut text = format!(
+        "{}–{}",
+        record.start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+        record.end.with_timezone(&Local).format("%H:%M")
+    );
+    if !record.task.is_empty() {
+        text.push_str(&format!(" {}", record.task));
+    }
+    if !record.tag.is_empty() {
+        text.push_str(&format!(" [{}]", record.tag));
+    }
+    text
+}
+
+/// Appends the previewed records to the history and returns how many were added.
+pub fn import(history: &mut History, preview: &ImportPreview) -> io::Result<usize> {
+    for record in &preview.records {
+        history.append(record.clone())?;
+    }
+    Ok(preview.records.len())
+}
+
+fn parse_local_time(text: &str, format: &str) -> Result<DateTime<Utc>, String> {
+    let time = NaiveDateTime::parse_from_str(text, format)
+        .map_err(|_| format!("Invalid time '{text}', expected format {format}"))?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("{text} does not exist in the local time zone"))
+}
+
+/// Parses minutes such as `25` or `24.5`, or a duration such as `0:25:00` or `25:00`.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration '{text}'");
+    if let Ok(minutes) = text.parse::<f64>() {
+        // `parse` also accepts `inf` and `NaN`, and huge values saturate the cast below.
+        let seconds = (minutes * 60.0).round();
+        return Some(seconds)
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+            .and_then(|seconds| Duration::try_seconds(seconds as i64))
+            .ok_or_else(invalid);
+    }
+    let parts = text
+        .split(':')
+        .map(|part| part.parse::<i64>().ok().filter(|part| *part >= 0).ok_or_else(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !(2..=3).contains(&parts.len()) {
+        return Err(invalid());
+    }
+    parts
+        .iter()
+        .try_fold(0_i64, |total, &part| total.checked_mul(60)?.checked_add(part))
+        .filter(|seconds| *seconds > 0)
+        .and_then(Duration::try_seconds)
+        .ok_or_else(invalid)
+}
+
+/// Splits CSV text into rows of fields, handling quoted fields with embedded delimiters, quotes and line breaks.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    parse_csv_lines(text).into_iter().map(|(_, row)| row).collect()
+}
+
+/// Like [`parse_csv`], but pairs each row with the line it starts on, counting from 1, so that
+/// rows after a quoted line break are still reported on the right line.
+fn parse_csv_lines(text: &str) -> Vec<(usize, Vec<String>)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line += 1;
+        }
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                row_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    rows
+}
@@ -2,20 +2,66 @@
 
 use eframe::egui::{self, epaint::{PathShape, Shape, Stroke, PathStroke}, Pos2, Vec2, Color32, Align2, FontId}; 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::f32::consts::TAU;
 
+pub mod alarm;
+pub mod ambient;
+pub mod audio;
+pub mod billing;
+pub mod budget;
+pub mod cli;
+pub mod commits;
+pub mod config;
+pub mod export;
+pub mod history;
+pub mod history_editor;
+pub mod import;
+pub mod quiet;
+pub mod report;
+pub mod sound;
+pub mod stats;
+pub mod taskwarrior;
+pub mod tick;
+pub mod todotxt;
+pub mod warning;
+
+use alarm::Alarm;
+use ambient::{AmbientLayer, AmbientSource, AmbientTrack, NoiseColor};
+use audio::{AudioService, AudioStatus};
+use billing::{BillingRounding, HourlyRate};
+use budget::{Budget, BudgetPeriod};
+use commits::{CommitLoader, LoadedCommits};
+use config::Config;
+use export::org::OrgGrouping;
+use export::{ExportFormat, ExportOptions};
+use history::{DateRange, History, PhaseOutcome, PhaseRecord};
+use history_editor::HistoryEditor;
+use import::{ImportForm, ImportPreset, ImportPreview};
+use report::ReportPeriod;
+use sound::{Sound, SoundEvent, SoundSlot, Volume};
+use taskwarrior::{Taskwarrior, TaskwarriorEvent, TaskwarriorTask, TaskwarriorWorker};
+use tick::{TickConfig, TickSource};
+use todotxt::{TodoList, TodoTask};
+
 // Constants for magic numbers
 const RADIUS_SCALE: f32 = 0.8;
 const STROKE_WIDTH_RATIO: f32 = 0.25;
 const REPAINT_INTERVAL_MS: u64 = 100;
-const BELL_VOLUME: f32 = 0.1;
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
 const FONT_SIZE_RATIO: f32 = 0.5;
 const QUARTER_TURN: f32 = TAU / 4.0;
+const WARNING_PULSE_STRENGTH: f32 = 0.5;
+const FLASH_DURATION_SECONDS: f32 = 1.5;
+const FLASH_MAX_ALPHA: f32 = 160.0;
 const MS_PER_MINUTE: f32 = 60_000.0;
 const SECONDS_PER_MINUTE: f32 = 60.0;
 const CIRCLE_POINTS: i32 = 100;
 const ICON_SIZE: u32 = 64;
 const TIMER_DISPLAY_SIZE: f32 = 250.0;
+const DEFAULT_FOCUS_RATING: u8 = 3;
+const MAX_FOCUS_RATING: u8 = 5;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TimerState {
@@ -24,19 +70,69 @@ pub enum TimerState {
     Stopped,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
 pub enum TimerPhase {
+    #[default]
     Pomodoro,
     ShortBreak,
     LongBreak,
 }
 
+impl TimerPhase {
+    /// Returns a human readable name for the phase.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimerPhase::Pomodoro => "Pomodoro",
+            TimerPhase::ShortBreak => "Short Break",
+            TimerPhase::LongBreak => "Long Break",
+        }
+    }
+}
+
 pub struct MyApp {
     pomodoro_min: f32,
     short_break_min: f32,
     cycles: i32,
     long_break_min: f32,
     play_sound: bool,
+    audio: AudioService,
+    /// Output devices offered in the settings, listed when the list is first opened after start or a refresh.
+    audio_devices: Option<Vec<String>>,
+    /// Files of the ambient layers, kept so that unchanged layers keep playing when others change.
+    ambient_sounds: BTreeMap<std::path::PathBuf, Sound>,
+    /// Layers and level last sent to the audio thread.
+    ambient_layers_sent: Option<Vec<AmbientLayer>>,
+    ambient_level_sent: f32,
+    show_ambient_window: bool,
+    /// Samples of one tick of the ticking clock.
+    tick_samples: std::sync::Arc<[f32]>,
+    /// Pomodoro start, pause time, settings and volume the ticking clock was last started with.
+    ticking_sent: Option<(DateTime<Utc>, f32, TickConfig, f32)>,
+    sound: Sound,
+    /// Default sound of the countdown.
+    beep: Sound,
+    /// End of the manual quiet override.
+    quiet_until: Option<DateTime<Utc>>,
+    /// When a silenced sound was last replaced by a flash of the window.
+    flash_started: Option<std::time::Instant>,
+    /// Attention to ask the system for in place of a sound that was silenced or failed to play.
+    attention_pending: Option<egui::UserAttentionType>,
+    /// Whether a failed alert is waiting to bring the window to the front.
+    focus_pending: bool,
+    /// Why audio is not working, shown as a status indicator until a device opens again.
+    audio_error: Option<String>,
+    show_quiet_window: bool,
+    /// The alarm repeating until the end of the last phase is acknowledged.
+    alarm: Option<Alarm>,
+    /// Start of the phase the warning was last played in.
+    warning_sent: Option<DateTime<Utc>>,
+    /// Phase start and second of the last countdown beep.
+    countdown_sent: Option<(DateTime<Utc>, u32)>,
+    /// Sounds of the events that have their own file.
+    event_sounds: BTreeMap<SoundEvent, Sound>,
+    /// Why the chosen sound file could not be used.
+    sound_error: Option<String>,
+    show_sounds_window: bool,
 
     timer_state: TimerState,
     current_phase: TimerPhase,
@@ -45,6 +141,63 @@ pub struct MyApp {
     pause_delta_min: f32, // Time spent in pause
     pause_start_time: Option<DateTime<Utc>>,
     last_remaining_time_seconds: i32,
+
+    tag: String,
+    task: String,
+    interruptions: u32,
+    intention: String,
+    show_intention_prompt: bool,
+    /// Whether the intention prompt has just opened and its text field should take the focus.
+    focus_intention: bool,
+    /// Id of the finished pomodoro that awaits a reflection.
+    pending_reflection: Option<String>,
+    reflection_rating: u8,
+    reflection_note: String,
+    history: History,
+    last_refresh: std::time::Instant,
+    status_message: Option<String>,
+
+    export_format: Option<ExportFormat>,
+    export_from: String,
+    export_to: String,
+    export_options: ExportOptions,
+
+    show_report_window: bool,
+    report_weekly: bool,
+    report_date: String,
+    history_editor: HistoryEditor,
+
+    config: Config,
+    config_path: Option<std::path::PathBuf>,
+    budget_warning: Option<String>,
+    /// Tag and minutes of budget used when the running pomodoro was last checked.
+    budget_progress: Option<(String, f32)>,
+    show_budget_window: bool,
+    show_repositories_window: bool,
+    commit_loader: CommitLoader,
+
+    todo_list: Option<TodoList>,
+    /// The todo.txt task the current task and tag were taken from.
+    todo_task: Option<TodoTask>,
+    /// Context the todo.txt picker is limited to, or `None` for all tasks.
+    todo_context: Option<String>,
+
+    taskwarrior: Option<TaskwarriorWorker>,
+    taskwarrior_tasks: Vec<TaskwarriorTask>,
+    /// The Taskwarrior task the current task and tag were taken from.
+    taskwarrior_task: Option<TaskwarriorTask>,
+    /// Whether the selected Taskwarrior task was started by the running pomodoro.
+    taskwarrior_started: bool,
+    show_taskwarrior_window: bool,
+    taskwarrior_command: String,
+
+    /// CSV file being imported with its contents.
+    import_file: Option<(std::path::PathBuf, String)>,
+    import_form: ImportForm,
+    import_preview: Option<Result<ImportPreview, String>>,
+    show_billing_window: bool,
+    billing_from: String,
+    billing_to: String,
 }
 
 impl Default for MyApp {
@@ -55,6 +208,28 @@ impl Default for MyApp {
             cycles: 4,
             long_break_min: 15.0,
             play_sound: true,
+            sound: Sound::bell(),
+            audio: AudioService::start(),
+            audio_devices: None,
+            ambient_sounds: BTreeMap::new(),
+            ambient_layers_sent: None,
+            ambient_level_sent: 0.0,
+            show_ambient_window: false,
+            tick_samples: tick::synthesized_tick(),
+            ticking_sent: None,
+            beep: Sound::beep(),
+            alarm: None,
+            quiet_until: None,
+            flash_started: None,
+            attention_pending: None,
+            focus_pending: false,
+            audio_error: None,
+            show_quiet_window: false,
+            warning_sent: None,
+            countdown_sent: None,
+            event_sounds: BTreeMap::new(),
+            sound_error: None,
+            show_sounds_window: false,
             timer_state: TimerState::Stopped,
             current_phase: TimerPhase::Pomodoro,
             current_cycle: 0,
@@ -62,11 +237,76 @@ impl Default for MyApp {
             pause_delta_min: 0.0,
             pause_start_time: None,
             last_remaining_time_seconds: 0,
+            tag: String::new(),
+            task: String::new(),
+            interruptions: 0,
+            intention: String::new(),
+            show_intention_prompt: false,
+            focus_intention: false,
+            pending_reflection: None,
+            reflection_rating: DEFAULT_FOCUS_RATING,
+            reflection_note: String::new(),
+            history: History::default(),
+            last_refresh: std::time::Instant::now(),
+            status_message: None,
+            export_format: None,
+            export_from: String::new(),
+            export_to: String::new(),
+            export_options: ExportOptions::default(),
+            show_report_window: false,
+            report_weekly: false,
+            report_date: String::new(),
+            history_editor: HistoryEditor::default(),
+            config: Config::default(),
+            config_path: None,
+            budget_warning: None,
+            budget_progress: None,
+            show_budget_window: false,
+            show_repositories_window: false,
+            commit_loader: CommitLoader::default(),
+            todo_list: None,
+            todo_task: None,
+            todo_context: None,
+            taskwarrior: None,
+            taskwarrior_tasks: Vec::new(),
+            taskwarrior_task: None,
+            taskwarrior_started: false,
+            show_taskwarrior_window: false,
+            taskwarrior_command: String::new(),
+            import_file: None,
+            import_form: ImportForm::default(),
+            import_preview: None,
+            show_billing_window: false,
+            billing_from: String::new(),
+            billing_to: String::new(),
         }
     }
 }
 
 impl MyApp {
+    /// Creates an app that records finished phases into the given history.
+    pub fn with_history(history: History) -> Self {
+        Self {
+            history,
+            ..Self::default()
+        }
+    }
+
+    /// Uses the given settings, saving changes to `path` when it is set.
+    pub fn with_config(self, config: Config, path: Option<std::path::PathBuf>) -> Self {
+        let mut app = Self {
+            config,
+            config_path: path,
+            ..self
+        };
+        app.open_todo_list();
+        app.open_taskwarrior();
+        app.load_sound();
+        app.load_tick();
+        app.audio.set_device(app.config.audio_device.clone());
+        app
+    }
+
     pub fn timer_state(&self) -> TimerState {
         self.timer_state
     }
@@ -98,6 +338,415 @@ impl MyApp {
     pub fn play_sound(&self) -> bool {
         self.play_sound
     }
+
+    pub fn sound(&self) -> &Sound {
+        &self.sound
+    }
+
+    pub fn sound_error(&self) -> Option<&str> {
+        self.sound_error.as_deref()
+    }
+
+    /// Uses the audio file at `path` for notifications, or the built-in bell for `None`, and saves the choice.
+    /// A file that cannot be decoded is rejected and the error shown instead.
+    pub fn set_sound_file(&mut self, path: Option<std::path::PathBuf>) {
+        self.sound = match &path {
+            Some(path) => match Sound::from_file(path) {
+                Ok(sound) => sound,
+                Err(err) => {
+                    self.sound_error = Some(err);
+                    return;
+                }
+            },
+            None => Sound::bell(),
+        };
+        self.sound_error = None;
+        self.config.sound_file = path;
+        self.save_config();
+    }
+
+    /// Uses the audio file at `path` for `event`, or the default sound for `None`, and saves the choice.
+    /// A file that cannot be decoded is rejected and the error shown instead.
+    pub fn set_event_sound_file(&mut self, event: SoundEvent, path: Option<std::path::PathBuf>) {
+        match &path {
+            Some(path) => match Sound::from_file(path) {
+                Ok(sound) => {
+                    self.event_sounds.insert(event, sound);
+                }
+                Err(err) => {
+                    self.sound_error = Some(err);
+                    return;
+                }
+            },
+            None => {
+                self.event_sounds.remove(&event);
+            }
+        }
+        self.sound_error = None;
+        let mut slot = self.config.sound_slot(event);
+        slot.file = path;
+        self.config.sounds.insert(event, slot);
+        self.save_config();
+    }
+
+    pub fn audio_device(&self) -> Option<&str> {
+        self.config.audio_device.as_deref()
+    }
+
+    /// Plays sounds on the output device named `device`, or the system default for `None`, and saves the choice.
+    pub fn set_audio_device(&mut self, device: Option<String>) {
+        self.audio.set_device(device.clone());
+        self.config.audio_device = device;
+        self.save_config();
+    }
+
+    pub fn volume(&self) -> Volume {
+        self.config.volume
+    }
+
+    /// Changes the master volumes without saving the settings.
+    pub fn set_volume(&mut self, volume: Volume) {
+        self.config.volume = Volume {
+            alert: volume.alert.clamp(0.0, 1.0),
+            ambient: volume.ambient.clamp(0.0, 1.0),
+            muted: volume.muted,
+        };
+    }
+
+    /// Mutes or unmutes all sounds, keeping the volumes, and saves the choice.
+    pub fn toggle_mute(&mut self) {
+        self.config.volume.muted = !self.config.volume.muted;
+        self.save_config();
+    }
+
+    pub fn ambient(&self) -> &ambient::AmbientConfig {
+        &self.config.ambient
+    }
+
+    /// Returns how loud the ambient sound should play now: fully during a running pomodoro,
+    /// at the duck volume while paused or on a break, and not at all when stopped or muted.
+    pub fn ambient_level(&self) -> f32 {
+        let ambient = &self.config.ambient;
+        if !ambient.enabled || ambient.layers.is_empty() {
+            return 0.0;
+        }
+        let level = match (self.timer_state, self.current_phase) {
+            (TimerState::Stopped, _) => 0.0,
+            (TimerState::Running, TimerPhase::Pomodoro) => 1.0,
+            _ => ambient.duck_volume,
+        };
+        self.config.volume.ambient_volume(level) * self.quiet_factor()
+    }
+
+    /// Sends changed ambient layers and the current level to the audio thread, which fades between them.
+    fn sync_ambient(&mut self) {
+        if self.ambient_layers_sent.as_ref() != Some(&self.config.ambient.layers) {
+            let layers = self.config.ambient.layers.clone();
+            self.ambient_sounds.retain(|path, _| {
+                layers.iter().any(|layer| layer.source == AmbientSource::File(path.clone()))
+            });
+            let mut tracks = Vec::new();
+            for layer in &layers {
+                let track = match &layer.source {
+                    AmbientSource::Noise(color) => AmbientTrack::Noise(*color),
+                    AmbientSource::File(path) => match self.ambient_sounds.get(path) {
+                        Some(sound) => AmbientTrack::File(sound.clone()),
+                        None => match Sound::from_file(path) {
+                            Ok(sound) => {
+                                self.ambient_sounds.insert(path.clone(), sound.clone());
+                                AmbientTrack::File(sound)
+                            }
+                            Err(err) => {
+                                self.sound_error = Some(err);
+                                continue;
+                            }
+                        },
+                    },
+                };
+                tracks.push((track, layer.volume));
+            }
+            self.audio.set_ambient(tracks);
+            self.ambient_layers_sent = Some(layers);
+        }
+        let level = self.ambient_level();
+        if level != self.ambient_level_sent {
+            self.audio.set_ambient_level(level);
+            self.ambient_level_sent = level;
+        }
+    }
+
+    pub fn ticking(&self) -> &TickConfig {
+        &self.config.ticking
+    }
+
+    /// Uses the audio file at `path` as the tick, or the synthesized click for `None`, and saves the choice.
+    /// A file that cannot be decoded is rejected and the error shown instead.
+    pub fn set_tick_file(&mut self, path: Option<std::path::PathBuf>) {
+        let samples = match &path {
+            Some(path) => match Sound::from_file(path).and_then(|sound| tick::tick_samples(&sound)) {
+                Ok(samples) => samples,
+                Err(err) => {
+                    self.sound_error = Some(err);
+                    return;
+                }
+            },
+            None => tick::synthesized_tick(),
+        };
+        self.tick_samples = samples;
+        self.ticking_sent = None;
+        self.sound_error = None;
+        self.config.ticking.file = path;
+        self.save_config();
+    }
+
+    fn load_tick(&mut self) {
+        self.tick_samples = match &self.config.ticking.file {
+            Some(path) => Sound::from_file(path)
+                .and_then(|sound| tick::tick_samples(&sound))
+                .unwrap_or_else(|err| {
+                    self.sound_error = Some(err);
+                    tick::synthesized_tick()
+                }),
+            None => tick::synthesized_tick(),
+        };
+    }
+
+    /// Returns whether the ticking clock should play now: during a running pomodoro with sound on.
+    pub fn is_ticking(&self) -> bool {
+        self.config.ticking.enabled
+            && self.play_sound
+            && !self.config.volume.muted
+            && self.quiet_factor() > 0.0
+            && self.timer_state == TimerState::Running
+            && self.current_phase == TimerPhase::Pomodoro
+    }
+
+    /// Starts the ticking clock when a pomodoro starts or resumes and stops it otherwise.
+    /// The audio thread schedules the ticks itself from the remaining time.
+    fn sync_ticking(&mut self) {
+        let volume = self.config.volume.alert_volume(self.config.ticking.volume) * self.quiet_factor();
+        let wanted = self
+            .phase_start_time
+            .filter(|_| self.is_ticking())
+            .map(|start| (start, self.pause_delta_min, self.config.ticking.clone(), volume));
+        if wanted == self.ticking_sent {
+            return;
+        }
+        if wanted.is_some() {
+            let remaining = std::time::Duration::from_secs_f32(self.get_remaining_time_minutes().max(0.0) * SECONDS_PER_MINUTE);
+            let source = TickSource::new(self.tick_samples.clone(), self.config.ticking.clone(), remaining);
+            self.audio.start_ticking(source, volume);
+        } else {
+            self.audio.stop_ticking();
+        }
+        self.ticking_sent = wanted;
+    }
+
+    /// Changes whether `event` plays a sound and how loud, without saving the settings.
+    pub fn set_sound_slot(&mut self, event: SoundEvent, enabled: bool, volume: f32) {
+        let mut slot = self.config.sound_slot(event);
+        slot.enabled = enabled;
+        slot.volume = volume.clamp(0.0, 1.0);
+        self.config.sounds.insert(event, slot);
+    }
+
+    /// Returns the sound and volume played for `event`, or `None` if the event is silent.
+    /// The last pomodoro of a cycle sounds like any other pomodoro unless its own slot is enabled.
+    pub fn event_sound(&self, event: SoundEvent) -> Option<(&Sound, f32)> {
+        let mut event = event;
+        let mut slot = self.config.sound_slot(event);
+        if event == SoundEvent::LastPomodoroEnd && !slot.enabled {
+            event = SoundEvent::PomodoroEnd;
+            slot = self.config.sound_slot(event);
+        }
+        if !slot.enabled {
+            return None;
+        }
+        Some((self.event_sounds.get(&event).unwrap_or(self.default_event_sound(event)), slot.volume))
+    }
+
+    pub fn alarm(&self) -> Option<&Alarm> {
+        self.alarm.as_ref()
+    }
+
+    /// Stops the alarm and stores how long it rang in the record of the phase that ended.
+    pub fn acknowledge_alarm(&mut self) {
+        self.stop_alarm(true);
+    }
+
+    /// Stops the alarm and stores in the record of the phase that ended how long it rang until it was
+    /// acknowledged, or that it was missed.
+    fn stop_alarm(&mut self, acknowledged: bool) {
+        let Some(alarm) = self.alarm.take() else {
+            return;
+        };
+        let Some(mut record) = alarm.record_id.and_then(|id| self.history.get(&id).cloned()) else {
+            return;
+        };
+        if acknowledged {
+            record.alarm_acknowledged_s = Some(alarm.started.elapsed().as_secs_f32());
+        } else {
+            record.alarm_missed = true;
+        }
+        if let Err(err) = self.history.update(record) {
+            self.status_message = Some(format!("Failed to save history: {err}"));
+        }
+    }
+
+    /// Repeats the phase-end sound of a ringing alarm, louder each time, until its maximum duration,
+    /// after which the alarm stops and is recorded as missed.
+    pub fn handle_alarm(&mut self) {
+        let Some(alarm) = &mut self.alarm else {
+            return;
+        };
+        if self.config.alarm.is_expired(alarm.started.elapsed()) {
+            self.stop_alarm(false);
+            return;
+        }
+        let due = self.config.alarm.repeats_due(alarm.started.elapsed());
+        if due <= alarm.repeats {
+            return;
+        }
+        alarm.repeats = due;
+        let event = alarm.event;
+        if self.play_sound
+            && let Some((sound, volume)) = self.event_sound(event)
+        {
+            let sound = sound.clone();
+            self.play_notification(&sound, alarm::escalated_volume(volume, due));
+        }
+    }
+
+    /// Returns whether sounds are softened or silenced now, by a schedule or the manual override.
+    pub fn is_quiet(&self) -> bool {
+        self.quiet_until.is_some_and(|until| Utc::now() < until)
+            || self.config.quiet.is_quiet_at(chrono::Local::now().naive_local())
+    }
+
+    pub fn quiet_until(&self) -> Option<DateTime<Utc>> {
+        self.quiet_until.filter(|until| Utc::now() < *until)
+    }
+
+    /// Keeps sounds quiet for the next hour, or ends a running override.
+    pub fn toggle_quiet_override(&mut self) {
+        self.quiet_until = match self.quiet_until() {
+            Some(_) => None,
+            None => Some(Utc::now() + quiet::OVERRIDE_DURATION),
+        };
+    }
+
+    /// Returns the fraction of the volume to play at: the quiet hours volume when quiet, else 1.
+    fn quiet_factor(&self) -> f32 {
+        if self.is_quiet() { self.config.quiet.volume } else { 1.0 }
+    }
+
+    /// Replaces a silenced sound with the visual cues chosen for quiet hours.
+    fn show_visual_cue(&mut self) {
+        if self.config.quiet.flash {
+            self.flash_started = Some(std::time::Instant::now());
+        }
+        if self.config.quiet.request_attention {
+            self.attention_pending.get_or_insert(egui::UserAttentionType::Informational);
+        }
+    }
+
+    /// Returns why audio is not working, if it is not.
+    pub fn audio_error(&self) -> Option<&str> {
+        self.audio_error.as_deref()
+    }
+
+    /// Takes in a report from the audio thread. When an alert could not be played, the user is alerted
+    /// by an urgent request for attention, by bringing the window to the front with a flash, and by
+    /// the terminal bell when started from a terminal.
+    pub fn handle_audio_status(&mut self, status: AudioStatus) {
+        match status {
+            AudioStatus::Connected(_) => self.audio_error = None,
+            AudioStatus::Notice(message) => self.sound_error = Some(message),
+            AudioStatus::Failed(message) => {
+                self.sound_error = Some(message.clone());
+                self.audio_error = Some(message);
+            }
+            AudioStatus::AlertFailed(message) => {
+                self.sound_error = Some(message.clone());
+                self.audio_error = Some(message);
+                self.flash_started = Some(std::time::Instant::now());
+                self.attention_pending = Some(egui::UserAttentionType::Critical);
+                self.focus_pending = true;
+                ring_terminal_bell();
+            }
+        }
+    }
+
+    /// Returns the sound of `event` when it has no file of its own.
+    fn default_event_sound(&self, event: SoundEvent) -> &Sound {
+        if event == SoundEvent::Countdown { &self.beep } else { &self.sound }
+    }
+
+    pub fn warning(&self) -> &warning::WarningConfig {
+        &self.config.warning
+    }
+
+    /// Returns whether the running phase is in its warning period, in which the doughnut pulses.
+    pub fn is_in_warning_period(&self) -> bool {
+        self.timer_state == TimerState::Running
+            && self.config.warning.in_warning_period(
+                self.current_phase,
+                self.get_current_phase_duration_minutes() * SECONDS_PER_MINUTE,
+                self.get_remaining_time_minutes() * SECONDS_PER_MINUTE,
+            )
+    }
+
+    /// Plays the warning once when the running phase enters its warning period,
+    /// and a beep for each of its final seconds
+    fn handle_phase_warnings(&mut self) {
+        let Some(phase_start) = self.phase_start_time.filter(|_| self.timer_state == TimerState::Running) else {
+            return;
+        };
+        if self.is_in_warning_period() && self.warning_sent != Some(phase_start) {
+            self.warning_sent = Some(phase_start);
+            self.play_event_sound(SoundEvent::Warning);
+        }
+        let remaining_sec = self.get_remaining_time_minutes() * SECONDS_PER_MINUTE;
+        if let Some(second) = self.config.warning.countdown_second(self.current_phase, remaining_sec)
+            && self.countdown_sent != Some((phase_start, second))
+        {
+            self.countdown_sent = Some((phase_start, second));
+            self.play_event_sound(SoundEvent::Countdown);
+        }
+    }
+
+    /// Returns the event of the current phase running out.
+    pub fn completion_event(&self) -> SoundEvent {
+        match self.current_phase {
+            TimerPhase::Pomodoro if self.current_cycle + 1 >= self.cycles => SoundEvent::LastPomodoroEnd,
+            TimerPhase::Pomodoro => SoundEvent::PomodoroEnd,
+            TimerPhase::ShortBreak => SoundEvent::ShortBreakEnd,
+            TimerPhase::LongBreak => SoundEvent::LongBreakEnd,
+        }
+    }
+
+    /// Loads the sound files named in the settings, falling back to the bell for any that cannot be decoded.
+    fn load_sound(&mut self) {
+        self.sound_error = None;
+        self.sound = match &self.config.sound_file {
+            Some(path) => Sound::from_file(path).unwrap_or_else(|err| {
+                self.sound_error = Some(err);
+                Sound::bell()
+            }),
+            None => Sound::bell(),
+        };
+        self.event_sounds.clear();
+        for (event, slot) in &self.config.sounds {
+            if let Some(path) = &slot.file {
+                match Sound::from_file(path) {
+                    Ok(sound) => {
+                        self.event_sounds.insert(*event, sound);
+                    }
+                    Err(err) => self.sound_error = Some(err),
+                }
+            }
+        }
+    }
     
     pub fn phase_start_time(&self) -> Option<DateTime<Utc>> {
         self.phase_start_time
@@ -111,6 +760,225 @@ impl MyApp {
         self.pause_delta_min
     }
 
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn set_tag(&mut self, tag: impl Into<String>) {
+        self.tag = tag.into();
+    }
+
+    pub fn task(&self) -> &str {
+        &self.task
+    }
+
+    pub fn set_task(&mut self, task: impl Into<String>) {
+        self.task = task.into();
+    }
+
+    pub fn interruptions(&self) -> u32 {
+        self.interruptions
+    }
+
+    pub fn intention(&self) -> &str {
+        &self.intention
+    }
+
+    pub fn set_intention(&mut self, intention: impl Into<String>) {
+        self.intention = intention.into();
+    }
+
+    pub fn show_intention_prompt(&self) -> bool {
+        self.show_intention_prompt
+    }
+
+    /// Returns the history id of the pomodoro waiting for a reflection, if any.
+    pub fn pending_reflection(&self) -> Option<&str> {
+        self.pending_reflection.as_deref()
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn todo_list(&self) -> Option<&TodoList> {
+        self.todo_list.as_ref()
+    }
+
+    pub fn todo_task(&self) -> Option<&TodoTask> {
+        self.todo_task.as_ref()
+    }
+
+    /// Attributes the following pomodoros to a todo.txt task, using its first `+project` as the tag.
+    pub fn select_todo_task(&mut self, task: TodoTask) {
+        self.clear_taskwarrior_task();
+        self.task = task.title();
+        if let Some(project) = task.projects.first() {
+            self.tag = project.clone();
+        }
+        self.todo_task = Some(task);
+    }
+
+    /// Marks the selected todo.txt task as done in the file.
+    pub fn complete_todo_task(&mut self) {
+        let (Some(list), Some(task)) = (&mut self.todo_list, &self.todo_task) else {
+            return;
+        };
+        match list.complete(task, chrono::Local::now().date_naive()) {
+            Ok(()) => {
+                self.status_message = Some(format!("Completed '{}'", task.title()));
+                self.todo_task = None;
+                self.task.clear();
+            }
+            Err(err) => self.status_message = Some(format!("Failed to update todo.txt: {err}")),
+        }
+    }
+
+    pub fn taskwarrior_tasks(&self) -> &[TaskwarriorTask] {
+        &self.taskwarrior_tasks
+    }
+
+    pub fn taskwarrior_task(&self) -> Option<&TaskwarriorTask> {
+        self.taskwarrior_task.as_ref()
+    }
+
+    /// Attributes the following pomodoros to a Taskwarrior task, using its project, or else its first tag, as the tag.
+    /// If a pomodoro is running, the new task is started in place of the previous one.
+    pub fn select_taskwarrior_task(&mut self, task: TaskwarriorTask) {
+        self.clear_taskwarrior_task();
+        self.todo_task = None;
+        self.task = task.description.clone();
+        if let Some(tag) = task.project.as_ref().or(task.tags.first()) {
+            self.tag = tag.clone();
+        }
+        self.taskwarrior_task = Some(task);
+        if self.timer_state == TimerState::Running && self.current_phase == TimerPhase::Pomodoro {
+            self.start_taskwarrior_task();
+        }
+    }
+
+    /// Reads the pending tasks from Taskwarrior again in the background.
+    pub fn reload_taskwarrior_tasks(&mut self) {
+        if let Some(taskwarrior) = &self.taskwarrior {
+            taskwarrior.reload();
+        }
+    }
+
+    /// Waits for the queued Taskwarrior commands to finish and applies their results.
+    pub fn wait_for_taskwarrior(&mut self) {
+        if let Some(taskwarrior) = &self.taskwarrior {
+            taskwarrior.wait();
+        }
+        self.receive_taskwarrior_events();
+    }
+
+    /// Applies the task lists and errors reported by the Taskwarrior worker.
+    fn receive_taskwarrior_events(&mut self) {
+        let Some(taskwarrior) = &self.taskwarrior else {
+            return;
+        };
+        for event in taskwarrior.events() {
+            match event {
+                TaskwarriorEvent::Tasks(tasks) => self.taskwarrior_tasks = tasks,
+                TaskwarriorEvent::Failed(message) => self.status_message = Some(message),
+            }
+        }
+    }
+
+    /// Sets up the Taskwarrior command named in the settings, if any.
+    fn open_taskwarrior(&mut self) {
+        self.clear_taskwarrior_task();
+        self.taskwarrior_tasks.clear();
+        self.taskwarrior = self
+            .config
+            .taskwarrior_command
+            .as_deref()
+            .map(|command| TaskwarriorWorker::spawn(Taskwarrior::new(command)));
+        self.taskwarrior_command = self
+            .config
+            .taskwarrior_command
+            .as_ref()
+            .map(|command| command.display().to_string())
+            .unwrap_or_else(|| taskwarrior::DEFAULT_COMMAND.to_string());
+        self.reload_taskwarrior_tasks();
+    }
+
+    /// Stops the selected Taskwarrior task if needed and forgets it.
+    fn clear_taskwarrior_task(&mut self) {
+        self.stop_taskwarrior_task();
+        self.taskwarrior_task = None;
+    }
+
+    fn start_taskwarrior_task(&mut self) {
+        if self.taskwarrior_started {
+            return;
+        }
+        let (Some(taskwarrior), Some(task)) = (&self.taskwarrior, &self.taskwarrior_task) else {
+            return;
+        };
+        taskwarrior.start(&task.uuid);
+        self.taskwarrior_started = true;
+    }
+
+    fn stop_taskwarrior_task(&mut self) {
+        if !self.taskwarrior_started {
+            return;
+        }
+        self.taskwarrior_started = false;
+        let (Some(taskwarrior), Some(task)) = (&self.taskwarrior, &self.taskwarrior_task) else {
+            return;
+        };
+        taskwarrior.stop(&task.uuid);
+    }
+
+    /// Annotates the selected Taskwarrior task with the number of pomodoros completed on it.
+    /// Pomodoros are counted by the task's UUID, so renaming the task or sharing its description does not matter.
+    fn annotate_taskwarrior_task(&mut self) {
+        let (Some(taskwarrior), Some(task)) = (&self.taskwarrior, &self.taskwarrior_task) else {
+            return;
+        };
+        let completed = self
+            .history
+            .records()
+            .iter()
+            .filter(|record| {
+                record.phase == TimerPhase::Pomodoro
+                    && record.outcome == PhaseOutcome::Completed
+                    && record.taskwarrior_uuid == task.uuid
+            })
+            .count();
+        let text = format!("Pomodoro {completed} completed");
+        taskwarrior.annotate(&task.uuid, &text);
+    }
+
+    /// Opens the todo.txt file named in the settings, if any.
+    fn open_todo_list(&mut self) {
+        self.todo_task = None;
+        self.todo_context = None;
+        self.todo_list = match &self.config.todo_txt {
+            Some(path) => match TodoList::open(path) {
+                Ok(list) => Some(list),
+                Err(err) => {
+                    self.status_message = Some(format!("Failed to read {}: {err}", path.display()));
+                    None
+                }
+            },
+            None => None,
+        };
+    }
+
+    pub fn budget_warning(&self) -> Option<&str> {
+        self.budget_warning.as_deref()
+    }
+
+    pub fn dismiss_budget_warning(&mut self) {
+        self.budget_warning = None;
+    }
+
     pub fn get_current_phase_duration_minutes(&self) -> f32 {
         let duration = self.get_phase_duration_minutes(self.current_phase);
         assert!(duration != 0.0, "Phase duration cannot be zero");
@@ -129,12 +997,11 @@ impl MyApp {
     pub fn get_spent_time_minutes(&self) -> f32 {
         if let Some(phase_start_time) = self.phase_start_time {
             if self.timer_state == TimerState::Paused {
-                if let Some(pause_start_time) = self.pause_start_time {
-                    let elapsed_duration = pause_start_time.signed_duration_since(phase_start_time).num_milliseconds() as f32 / MS_PER_MINUTE;
-                    return elapsed_duration - self.pause_delta_min;
-                } else {
+                let Some(pause_start_time) = self.pause_start_time else {
                     return 0.0;
-                }
+                };
+                let elapsed_duration = pause_start_time.signed_duration_since(phase_start_time).num_milliseconds() as f32 / MS_PER_MINUTE;
+                return elapsed_duration - self.pause_delta_min;
             } else {
                 let elapsed_total = Utc::now().signed_duration_since(phase_start_time).num_milliseconds() as f32 / MS_PER_MINUTE;
                 return elapsed_total - self.pause_delta_min;
@@ -157,6 +1024,9 @@ impl MyApp {
     pub fn begin_timer_with_phase(&mut self, phase: TimerPhase) {
         self.timer_state = TimerState::Running;
         self.begin_phase(phase);
+        if phase == TimerPhase::Pomodoro {
+            self.warn_if_budget_exhausted();
+        }
     }
     
     /// Starts a new timer phase, resetting pause state and setting start time.
@@ -165,12 +1035,20 @@ impl MyApp {
         self.phase_start_time = Some(Utc::now());
         self.pause_delta_min = 0.0;
         self.pause_start_time = None;
+        self.interruptions = 0;
+        self.intention.clear();
+        self.show_intention_prompt = phase == TimerPhase::Pomodoro && self.timer_state == TimerState::Running;
+        self.focus_intention = self.show_intention_prompt;
+        if self.show_intention_prompt {
+            self.start_taskwarrior_task();
+        }
     }
     
     pub fn pause_timer(&mut self) {
         assert!(self.timer_state == TimerState::Running, "Cannot pause a timer that is not running.");
         self.pause_start_time = Some(Utc::now());
         self.timer_state = TimerState::Paused;
+        self.stop_taskwarrior_task();
     }
     
     pub fn resume_timer(&mut self) {
@@ -181,9 +1059,17 @@ impl MyApp {
             self.pause_start_time = None;
         }
         self.timer_state = TimerState::Running;
+        if self.current_phase == TimerPhase::Pomodoro {
+            self.start_taskwarrior_task();
+        }
     }
     
     pub fn reset_timer(&mut self) {
+        // Acting on the timer means the user noticed the alarm.
+        self.stop_alarm(true);
+        if self.timer_state != TimerState::Stopped {
+            self.record_phase(PhaseOutcome::Voided);
+        }
         self.timer_state = TimerState::Stopped;
         self.phase_start_time = None;
         self.pause_start_time = None;
@@ -192,26 +1078,33 @@ impl MyApp {
         self.current_phase = TimerPhase::Pomodoro;
     }
 
-    /// Plays the bell sound notification in a separate thread to avoid blocking the UI.
+    /// Plays the sound of `event` through the audio thread.
     /// Only plays if `play_sound` is enabled.
-    pub fn play_bell_sound(&self) {
-        if self.play_sound {
-            let bell_data = include_bytes!("../resources/bell.mp3");
-            
-            std::thread::spawn(move || {
-                use std::io::Cursor;
-                use rodio::{Decoder, OutputStream, Sink};
-                
-                if let Ok((_stream, stream_handle)) = OutputStream::try_default() {
-                    if let Ok(sink) = Sink::try_new(&stream_handle) {
-                        if let Ok(source) = Decoder::new(Cursor::new(bell_data)) {
-                            sink.append(source);
-                            sink.set_volume(BELL_VOLUME); // Set smaller volume
-                            sink.sleep_until_end();
-                        }
-                    }
-                }
-            });
+    pub fn play_event_sound(&mut self, event: SoundEvent) {
+        if !self.play_sound {
+            return;
+        }
+        if let Some((sound, volume)) = self.event_sound(event) {
+            let sound = sound.clone();
+            self.play_notification(&sound, volume);
+        }
+    }
+
+    /// Plays `sound` scaled by the alert volume, falling back to the built-in bell and showing the error
+    /// if it fails to decode. Nothing plays while muted.
+    fn play_notification(&mut self, sound: &Sound, volume: f32) {
+        if self.config.volume.muted {
+            return;
+        }
+        let factor = self.quiet_factor();
+        if factor == 0.0 {
+            self.show_visual_cue();
+            return;
+        }
+        let volume = self.config.volume.alert_volume(volume) * factor;
+        if let Err(err) = self.audio.play(sound, volume) {
+            self.sound_error = Some(format!("Failed to play {}: {err}", sound.name()));
+            let _ = self.audio.play(&Sound::bell(), volume);
         }
     }
     
@@ -236,31 +1129,180 @@ impl MyApp {
         self.begin_phase(next_phase);
     }
 
-    /// Skips the current phase and moves to the next phase
-    pub fn skip_phase(&mut self) {
-        if self.timer_state != TimerState::Stopped {
-            self.next_phase();
+    /// Records an interruption of the running pomodoro.
+    pub fn record_interruption(&mut self) {
+        if self.timer_state != TimerState::Stopped && self.current_phase == TimerPhase::Pomodoro {
+            self.interruptions += 1;
         }
     }
 
-    /// Skips the current cycle and moves to the next cycle
-    pub fn skip_cycle(&mut self) {
-        if self.timer_state != TimerState::Stopped {
-            match self.current_phase {
-                TimerPhase::Pomodoro => {
-                    self.current_cycle += 1;
-                    if self.current_cycle >= self.cycles {
-                        self.current_cycle = 0;
-                        self.begin_phase(TimerPhase::LongBreak);
-                    } else {
-                        self.begin_phase(TimerPhase::Pomodoro);
-                    }
-                }
-                TimerPhase::ShortBreak => {
-                    self.begin_phase(TimerPhase::Pomodoro);
-                }
-                TimerPhase::LongBreak => {
-                    self.current_cycle = 0;
+    /// Appends the current phase to the history with the given outcome and returns its id.
+    /// Failures to write are reported in the status line instead of interrupting the timer.
+    fn record_phase(&mut self, outcome: PhaseOutcome) -> Option<String> {
+        let start = self.phase_start_time?;
+        let end = Utc::now();
+        let mut pause_min = self.pause_delta_min;
+        if let Some(pause_start_time) = self.pause_start_time {
+            pause_min += end.signed_duration_since(pause_start_time).num_milliseconds() as f32 / MS_PER_MINUTE;
+        }
+        let record = PhaseRecord {
+            id: String::new(),
+            start,
+            end,
+            phase: self.current_phase,
+            planned_min: self.get_current_phase_duration_minutes(),
+            actual_min: self.get_spent_time_minutes().max(0.0),
+            pause_min,
+            outcome,
+            tag: self.tag.trim().to_string(),
+            task: self.task.trim().to_string(),
+            interruptions: self.interruptions,
+            intention: self.intention.trim().to_string(),
+            focus_rating: None,
+            note: String::new(),
+            taskwarrior_uuid: self.taskwarrior_task.as_ref().map(|task| task.uuid.clone()).unwrap_or_default(),
+            alarm_acknowledged_s: None,
+            alarm_missed: false,
+            utc_offset_s: Some(history::local_offset_s(start)),
+        };
+        self.stop_taskwarrior_task();
+        match self.history.append(record) {
+            Ok(id) => Some(id),
+            Err(err) => {
+                self.status_message = Some(format!("Failed to save history: {err}"));
+                None
+            }
+        }
+    }
+
+    /// Finishes the current phase as completed and moves on to the next one.
+    /// A finished pomodoro is queued for a reflection, and the alarm starts ringing if enabled.
+    pub fn complete_phase(&mut self) {
+        if self.timer_state == TimerState::Stopped {
+            return;
+        }
+        // An alarm still ringing for the previous phase was missed; the new one replaces it.
+        self.stop_alarm(false);
+        let finished_phase = self.current_phase;
+        let event = self.completion_event();
+        let today = chrono::Local::now().date_naive();
+        // Thresholds already crossed while the pomodoro ran are not reported again.
+        let progress = self.budget_progress.take().filter(|(tag, _)| *tag == self.tag).map(|(_, used)| used);
+        let budget = self
+            .current_budget()
+            .filter(|_| finished_phase == TimerPhase::Pomodoro)
+            .map(|budget| (budget.clone(), progress.unwrap_or_else(|| budget.used_min(self.history.records(), today))));
+        let id = self.record_phase(PhaseOutcome::Completed);
+        if self.config.alarm.enabled {
+            self.alarm = Some(Alarm::new(event, id.clone()));
+        }
+        if finished_phase == TimerPhase::Pomodoro && id.is_some() {
+            self.annotate_taskwarrior_task();
+        }
+        if let Some((budget, used_before)) = budget {
+            let used_after = budget.used_min(self.history.records(), today);
+            if let Some(warning) = budget::threshold_warning(&budget, used_before, used_after) {
+                self.budget_warning = Some(warning);
+            }
+        }
+        if finished_phase == TimerPhase::Pomodoro && id.is_some() {
+            self.pending_reflection = id;
+            self.reflection_rating = DEFAULT_FOCUS_RATING;
+            self.reflection_note.clear();
+        }
+        self.next_phase();
+    }
+
+    /// Stores the focus rating and note for the pomodoro awaiting a reflection.
+    pub fn submit_reflection(&mut self, rating: u8, note: &str) {
+        let Some(id) = self.pending_reflection.take() else {
+            return;
+        };
+        // The record may have been deleted in the meantime, possibly on another machine.
+        let Some(mut record) = self.history.get(&id).cloned() else {
+            return;
+        };
+        record.focus_rating = Some(rating.clamp(1, MAX_FOCUS_RATING));
+        record.note = note.trim().to_string();
+        if let Err(err) = self.history.update(record) {
+            self.status_message = Some(format!("Failed to save reflection: {err}"));
+        }
+    }
+
+    /// Returns the budget for the current tag, if any.
+    fn current_budget(&self) -> Option<&Budget> {
+        budget::find(&self.config.budgets, &self.tag)
+    }
+
+    /// Warns when the running pomodoro takes its tag's budget past 80% or 100%.
+    fn check_budget_progress(&mut self) {
+        self.update_budget_progress(self.get_spent_time_minutes());
+    }
+
+    /// Warns when `spent_min` of the running pomodoro take its tag's budget past 80% or 100%
+    /// since the last update.
+    pub fn update_budget_progress(&mut self, spent_min: f32) {
+        let used = match self.current_budget() {
+            Some(budget) if self.timer_state != TimerState::Stopped && self.current_phase == TimerPhase::Pomodoro => {
+                let used_min =
+                    budget.used_min(self.history.records(), chrono::Local::now().date_naive()) + spent_min.max(0.0);
+                let before = self.budget_progress.as_ref().filter(|(tag, _)| *tag == self.tag).map(|(_, used)| *used);
+                let warning = before.and_then(|before| budget::threshold_warning(budget, before, used_min));
+                if warning.is_some() {
+                    self.budget_warning = warning;
+                }
+                Some((self.tag.clone(), used_min))
+            }
+            _ => None,
+        };
+        self.budget_progress = used;
+    }
+
+    /// Warns when a pomodoro is started on a tag whose budget is already used up.
+    fn warn_if_budget_exhausted(&mut self) {
+        let Some(budget) = self.current_budget() else {
+            return;
+        };
+        let used_min = budget.used_min(self.history.records(), chrono::Local::now().date_naive());
+        if used_min >= budget.limit_min() {
+            self.budget_warning = Some(format!("Budget exhausted. {}", budget.progress_text(used_min)));
+        }
+    }
+
+    /// Dismisses the reflection prompt without storing anything.
+    pub fn skip_reflection(&mut self) {
+        self.pending_reflection = None;
+    }
+
+    /// Skips the current phase and moves to the next phase
+    pub fn skip_phase(&mut self) {
+        self.stop_alarm(true);
+        if self.timer_state != TimerState::Stopped {
+            self.record_phase(PhaseOutcome::Skipped);
+            self.next_phase();
+        }
+    }
+
+    /// Skips the current cycle and moves to the next cycle
+    pub fn skip_cycle(&mut self) {
+        self.stop_alarm(true);
+        if self.timer_state != TimerState::Stopped {
+            self.record_phase(PhaseOutcome::Skipped);
+            match self.current_phase {
+                TimerPhase::Pomodoro => {
+                    self.current_cycle += 1;
+                    if self.current_cycle >= self.cycles {
+                        self.current_cycle = 0;
+                        self.begin_phase(TimerPhase::LongBreak);
+                    } else {
+                        self.begin_phase(TimerPhase::Pomodoro);
+                    }
+                }
+                TimerPhase::ShortBreak => {
+                    self.begin_phase(TimerPhase::Pomodoro);
+                }
+                TimerPhase::LongBreak => {
+                    self.current_cycle = 0;
                     self.begin_phase(TimerPhase::Pomodoro);
                 }
             }
@@ -289,6 +1331,11 @@ impl MyApp {
 
         if self.timer_state == TimerState::Paused {
             remaining_color = Color32::from_gray(150); // Diminished color when paused
+        } else if self.is_in_warning_period() {
+            // Pulse towards white once a second as the end approaches
+            let time = ui.input(|input| input.time) as f32;
+            let pulse = ((time * TAU).sin() + 1.0) / 2.0 * WARNING_PULSE_STRENGTH;
+            remaining_color = remaining_color.lerp_to_gamma(Color32::WHITE, pulse);
         }
 
         let spent_color = Color32::from_gray(80); // Darker gray for spent time
@@ -400,11 +1447,44 @@ impl MyApp {
     fn handle_timer_completion(&mut self) {
         let remaining_time = self.get_remaining_time_minutes();
         if self.timer_state == TimerState::Running && remaining_time <= 0.0 {
-            self.play_bell_sound();
-            self.next_phase();
+            self.play_event_sound(self.completion_event());
+            self.complete_phase();
         }
     }
     
+    /// Shows problems with the output device reported by the audio thread
+    fn handle_audio_statuses(&mut self) {
+        for status in self.audio.take_statuses() {
+            self.handle_audio_status(status);
+        }
+    }
+
+    /// Picks up journal entries written by other machines sharing the data folder
+    /// and edits made to the todo.txt file by other programs
+    fn refresh_external_files(&mut self) {
+        if self.last_refresh.elapsed() < REFRESH_INTERVAL {
+            return;
+        }
+        self.last_refresh = std::time::Instant::now();
+        if let Err(err) = self.history.refresh() {
+            self.status_message = Some(format!("Failed to read history: {err}"));
+        }
+        if let Some(list) = &mut self.todo_list {
+            match list.refresh() {
+                Ok(true) => {
+                    // Forget the selected task if it was completed or changed elsewhere.
+                    if let Some(task) = &self.todo_task
+                        && !list.open_tasks().contains(&task)
+                    {
+                        self.todo_task = None;
+                    }
+                }
+                Ok(false) => {}
+                Err(err) => self.status_message = Some(format!("Failed to read todo.txt: {err}")),
+            }
+        }
+    }
+
     /// Updates the window icon if the remaining time has changed
     fn update_window_icon(&mut self, ctx: &egui::Context) {
         let remaining_time = self.get_remaining_time_minutes();
@@ -442,6 +1522,77 @@ impl MyApp {
                 ui.label("Play Sound");
                 ui.add_enabled(settings_enabled, egui::Checkbox::new(&mut self.play_sound, "Play Sound"));
             });
+            let mut volume = self.config.volume;
+            let mut save_volume = false;
+            ui.horizontal(|ui| {
+                ui.label("Alert volume");
+                let response = ui.add_enabled(!volume.muted, egui::Slider::new(&mut volume.alert, 0.0..=1.0));
+                save_volume |= response.drag_stopped() || (response.changed() && !response.dragged());
+                if ui.add_enabled(!volume.muted, egui::Button::new("Test")).clicked() {
+                    self.play_notification(&self.sound.clone(), sound::DEFAULT_VOLUME);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Ambient volume");
+                let response = ui.add_enabled(!volume.muted, egui::Slider::new(&mut volume.ambient, 0.0..=1.0));
+                save_volume |= response.drag_stopped() || (response.changed() && !response.dragged());
+            });
+            if volume != self.config.volume {
+                self.set_volume(volume);
+            }
+            if save_volume {
+                self.save_config();
+            }
+            ui.horizontal(|ui| {
+                ui.label("Output device");
+                let mut device = self.config.audio_device.clone();
+                egui::ComboBox::from_id_salt("audio_device")
+                    .selected_text(device.as_deref().unwrap_or("System default"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut device, None, "System default");
+                        // Listing devices can take a while, so it only happens once the list is opened.
+                        for name in self.audio_devices.get_or_insert_with(audio::output_devices).iter() {
+                            ui.selectable_value(&mut device, Some(name.clone()), name);
+                        }
+                    });
+                if device != self.config.audio_device {
+                    self.set_audio_device(device);
+                }
+                if ui.button("Refresh").clicked() {
+                    self.audio_devices = None;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Sound");
+                ui.label(self.sound.name());
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Choose…").clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Audio", &sound::SOUND_EXTENSIONS)
+                        .pick_file()
+                {
+                    self.set_sound_file(Some(path));
+                }
+                if ui.add_enabled(self.config.sound_file.is_some(), egui::Button::new("Use bell")).clicked() {
+                    self.set_sound_file(None);
+                }
+                if ui.button("Preview").clicked() {
+                    self.play_notification(&self.sound.clone(), sound::DEFAULT_VOLUME);
+                }
+                if ui.button("Events…").clicked() {
+                    self.show_sounds_window = true;
+                }
+                if ui.button("Ambient…").clicked() {
+                    self.show_ambient_window = true;
+                }
+                if ui.button("Quiet hours…").clicked() {
+                    self.show_quiet_window = true;
+                }
+            });
+            if let Some(error) = &self.sound_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
         });
     }
     
@@ -479,8 +1630,10 @@ impl MyApp {
             if pause_resume_button_response.clicked() {
                 if self.timer_state == TimerState::Running {
                     self.pause_timer();
+                    self.play_event_sound(SoundEvent::Pause);
                 } else {
                     self.resume_timer();
+                    self.play_event_sound(SoundEvent::Resume);
                 }
             }
             
@@ -492,9 +1645,11 @@ impl MyApp {
                     
                     if ui.button("⏭️ Skip this phase").clicked() {
                         self.skip_phase();
+                        self.play_event_sound(SoundEvent::Skip);
                         ui.close_menu();
                     }else if ui.button("⏩ Skip this cycle").clicked() {
                         self.skip_cycle();
+                        self.play_event_sound(SoundEvent::Skip);
                         ui.close_menu();
                     }
                 });
@@ -503,31 +1658,1150 @@ impl MyApp {
                 self.reset_timer();
             }
         });
+        // Sound controls get their own row so that the timer buttons fit the narrow window.
+        ui.horizontal(|ui| {
+            let mute_text = if self.config.volume.muted { "🔇 Unmute" } else { "🔊 Mute" };
+            if ui.button(mute_text).clicked() {
+                self.toggle_mute();
+            }
+            let quiet_text = match self.quiet_until() {
+                Some(until) => format!("🌙 Quiet until {}", until.with_timezone(&chrono::Local).format("%H:%M")),
+                None => "🌙 Quiet 1 h".to_string(),
+            };
+            if ui
+                .selectable_label(self.quiet_until().is_some(), quiet_text)
+                .on_hover_text("Soften or silence sounds for the next hour")
+                .clicked()
+            {
+                self.toggle_quiet_override();
+            }
+        });
     }
     
+    /// Renders the task and tag inputs along with the interruption counter
+    fn render_task_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Task");
+            if ui.text_edit_singleline(&mut self.task).changed() {
+                self.todo_task = None;
+                self.clear_taskwarrior_task();
+            }
+        });
+        if let Some(list) = &self.todo_list {
+            let mut selected = None;
+            let mut complete = false;
+            let contexts = list.contexts();
+            if !contexts.is_empty() {
+                let current = self.todo_context.as_deref().map_or("All contexts".to_string(), |context| format!("@{context}"));
+                egui::ComboBox::from_id_salt("todo_txt_context")
+                    .selected_text(current)
+                    .width(180.0)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.todo_context, None, "All contexts");
+                        for context in contexts {
+                            ui.selectable_value(&mut self.todo_context, Some(context.to_string()), format!("@{context}"));
+                        }
+                    });
+            }
+            ui.horizontal(|ui| {
+                let current = self.todo_task.as_ref().map(|task| task.description.as_str()).unwrap_or("Pick from todo.txt");
+                egui::ComboBox::from_id_salt("todo_txt_task")
+                    .selected_text(current)
+                    .width(180.0)
+                    .show_ui(ui, |ui| {
+                        for task in list.open_tasks_in(self.todo_context.as_deref()) {
+                            let label = match task.priority {
+                                Some(priority) => format!("({priority}) {}", task.description),
+                                None => task.description.clone(),
+                            };
+                            if ui.selectable_label(self.todo_task.as_ref() == Some(task), label).clicked() {
+                                selected = Some(task.clone());
+                            }
+                        }
+                    });
+                complete = ui.add_enabled(self.todo_task.is_some(), egui::Button::new("Done")).clicked();
+            });
+            if let Some(task) = selected {
+                self.select_todo_task(task);
+            }
+            if complete {
+                self.complete_todo_task();
+            }
+        }
+        if self.taskwarrior.is_some() {
+            let mut selected = None;
+            let mut reload = false;
+            ui.horizontal(|ui| {
+                let current = self
+                    .taskwarrior_task
+                    .as_ref()
+                    .map(|task| task.description.as_str())
+                    .unwrap_or("Pick from Taskwarrior");
+                egui::ComboBox::from_id_salt("taskwarrior_task")
+                    .selected_text(current)
+                    .width(180.0)
+                    .show_ui(ui, |ui| {
+                        for task in &self.taskwarrior_tasks {
+                            let label = match &task.project {
+                                Some(project) => format!("{} [{project}]", task.description),
+                                None => task.description.clone(),
+                            };
+                            let is_selected = self.taskwarrior_task.as_ref().is_some_and(|current| current.uuid == task.uuid);
+                            if ui.selectable_label(is_selected, label).clicked() {
+                                selected = Some(task.clone());
+                            }
+                        }
+                    });
+                reload = ui.button("Reload").clicked();
+            });
+            if let Some(task) = selected {
+                self.select_taskwarrior_task(task);
+            }
+            if reload {
+                self.reload_taskwarrior_tasks();
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Tag");
+            ui.add(egui::TextEdit::singleline(&mut self.tag).desired_width(100.0));
+            let can_interrupt = self.timer_state != TimerState::Stopped && self.current_phase == TimerPhase::Pomodoro;
+            let interrupt_text = format!("Interrupted ({})", self.interruptions);
+            if ui.add_enabled(can_interrupt, egui::Button::new(interrupt_text)).clicked() {
+                self.record_interruption();
+            }
+        });
+    }
+
+    /// Renders the menu bar at the top of the window
+    fn render_menu_bar(&mut self, ui: &mut egui::Ui) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                for format in [ExportFormat::Csv, ExportFormat::ICalendar, ExportFormat::Org] {
+                    if ui.button(format!("Export {}…", format.name())).clicked() {
+                        self.export_format = Some(format);
+                        ui.close_menu();
+                    }
+                }
+                if ui.button("Export to Timewarrior").clicked() {
+                    self.export_to_timewarrior();
+                    ui.close_menu();
+                }
+                if ui.button("Import CSV…").clicked() {
+                    ui.close_menu();
+                    self.open_import_file();
+                }
+                ui.separator();
+                if ui.button("History…").clicked() {
+                    self.history_editor.open = true;
+                    ui.close_menu();
+                }
+                if ui.button("Report…").clicked() {
+                    self.show_report_window = true;
+                    ui.close_menu();
+                }
+                if ui.button("Budgets…").clicked() {
+                    self.show_budget_window = true;
+                    ui.close_menu();
+                }
+                if ui.button("Billing…").clicked() {
+                    self.show_billing_window = true;
+                    ui.close_menu();
+                }
+                if ui.button("Git repositories…").clicked() {
+                    self.show_repositories_window = true;
+                    ui.close_menu();
+                }
+                if ui.button("Taskwarrior…").clicked() {
+                    self.show_taskwarrior_window = true;
+                    ui.close_menu();
+                }
+                if ui.button("Open todo.txt…").clicked() {
+                    ui.close_menu();
+                    if let Some(path) = rfd::FileDialog::new().add_filter("todo.txt", &["txt"]).pick_file() {
+                        self.config.todo_txt = Some(path);
+                        self.save_config();
+                        self.open_todo_list();
+                    }
+                }
+            });
+        });
+    }
+
+    /// Renders the export window with its date range filter and format options
+    fn render_export_window(&mut self, ctx: &egui::Context) {
+        let Some(format) = self.export_format else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("Export {}", format.name()))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Date range (YYYY-MM-DD, empty for no limit)");
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.add(egui::TextEdit::singleline(&mut self.export_from).desired_width(80.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("To");
+                    ui.add(egui::TextEdit::singleline(&mut self.export_to).desired_width(80.0));
+                });
+                match format {
+                    ExportFormat::ICalendar => {
+                        ui.checkbox(&mut self.export_options.merge_consecutive, "Merge consecutive pomodoros");
+                    }
+                    ExportFormat::Org => {
+                        ui.horizontal(|ui| {
+                            ui.label("Headings per");
+                            ui.radio_value(&mut self.export_options.org_grouping, OrgGrouping::Task, "Task");
+                            ui.radio_value(&mut self.export_options.org_grouping, OrgGrouping::Tag, "Tag");
+                        });
+                    }
+                    ExportFormat::Csv => {}
+                }
+                if ui.button("Export…").clicked() {
+                    match DateRange::parse(&self.export_from, &self.export_to) {
+                        Ok(range) => self.export_with_dialog(format, &range),
+                        Err(err) => self.status_message = Some(err),
+                    }
+                }
+            });
+        if !open {
+            self.export_format = None;
+        }
+    }
+
+    /// Asks for a destination file and writes the history within `range` to it
+    fn export_with_dialog(&mut self, format: ExportFormat, range: &DateRange) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.name(), &[format.extension()])
+            .set_file_name(format!("pomodoro-history.{}", format.extension()))
+            .save_file()
+        else {
+            return;
+        };
+        let result = format.export_to_file(&path, self.history.records(), range, &self.export_options);
+        self.status_message = Some(match result {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(err) => format!("Export failed: {err}"),
+        });
+        self.export_format = None;
+    }
+
+    /// Adds all completed pomodoros to the default Timewarrior database
+    fn export_to_timewarrior(&mut self) {
+        let Some(database_dir) = export::timewarrior::default_database_dir() else {
+            self.status_message = Some("No Timewarrior database directory found".to_string());
+            return;
+        };
+        let result = export::timewarrior::export_timewarrior(&database_dir, self.history.records(), &DateRange::default());
+        self.status_message = Some(match result {
+            Ok(added) => format!("Added {added} intervals to Timewarrior"),
+            Err(err) => format!("Timewarrior export failed: {err}"),
+        });
+    }
+
+    /// Renders the report window for producing a daily or weekly Markdown summary
+    fn render_report_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_report_window;
+        egui::Window::new("Report")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.report_weekly, false, "Day");
+                    ui.radio_value(&mut self.report_weekly, true, "Week");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Date");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.report_date)
+                            .hint_text("today")
+                            .desired_width(80.0),
+                    );
+                });
+                // Starts reading the commits of the period as soon as it is selected.
+                let loading = self.commit_loader.is_enabled()
+                    && self
+                        .report_period()
+                        .ok()
+                        .and_then(|period| period.range().from)
+                        .is_some_and(|from| self.commit_loader.commits_since(from).is_none());
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!loading, egui::Button::new("Copy")).clicked()
+                        && let Some(markdown) = self.build_report()
+                    {
+                        ctx.copy_text(markdown);
+                        self.status_message = Some("Report copied to clipboard".to_string());
+                    }
+                    if ui.add_enabled(!loading, egui::Button::new("Save…")).clicked()
+                        && let Some(markdown) = self.build_report()
+                    {
+                        self.save_report_with_dialog(&markdown);
+                    }
+                    if loading {
+                        ui.spinner();
+                        ui.label("Loading commits…");
+                    }
+                });
+            });
+        self.show_report_window = open;
+    }
+
+    /// Returns the day or week selected in the report window
+    fn report_period(&self) -> Result<ReportPeriod, String> {
+        let date = history::parse_date(&self.report_date)?.unwrap_or_else(|| chrono::Local::now().date_naive());
+        Ok(if self.report_weekly { ReportPeriod::Week(date) } else { ReportPeriod::Day(date) })
+    }
+
+    /// Builds the Markdown report selected in the report window, reporting invalid dates in the status line
+    fn build_report(&mut self) -> Option<String> {
+        let period = match self.report_period() {
+            Ok(period) => period,
+            Err(err) => {
+                self.status_message = Some(err);
+                return None;
+            }
+        };
+        let loaded = match period.range().from.filter(|_| self.commit_loader.is_enabled()) {
+            Some(from) => match self.commit_loader.commits_since(from) {
+                Some(loaded) => loaded.clone(),
+                None => {
+                    self.status_message = Some("Commits are still loading".to_string());
+                    return None;
+                }
+            },
+            None => LoadedCommits::default(),
+        };
+        if let Some(error) = loaded.errors.first() {
+            self.status_message = Some(error.clone());
+        }
+        Some(report::markdown_report_with_commits(self.history.records(), &loaded.commits, &period))
+    }
+
+    /// Asks for a destination file and writes the report to it
+    fn save_report_with_dialog(&mut self, markdown: &str) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Markdown", &["md"])
+            .set_file_name("pomodoro-report.md")
+            .save_file()
+        else {
+            return;
+        };
+        self.status_message = Some(match std::fs::write(&path, markdown) {
+            Ok(()) => format!("Report saved to {}", path.display()),
+            Err(err) => format!("Saving report failed: {err}"),
+        });
+    }
+
+    /// Renders the window for editing the focus-time budgets per tag
+    fn render_budget_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_budget_window;
+        egui::Window::new("Budgets")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut to_remove = None;
+                egui::Grid::new("budgets").show(ui, |ui| {
+                    for header in ["Tag", "Hours", "Per", ""] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for (index, budget) in self.config.budgets.iter_mut().enumerate() {
+                        ui.add(egui::TextEdit::singleline(&mut budget.tag).desired_width(90.0));
+                        ui.add(egui::DragValue::new(&mut budget.hours).range(0.5..=200.0).speed(0.5));
+                        egui::ComboBox::from_id_salt(("budget_period", index))
+                            .selected_text(budget.period.name())
+                            .show_ui(ui, |ui| {
+                                for period in [BudgetPeriod::Week, BudgetPeriod::Month] {
+                                    ui.selectable_value(&mut budget.period, period, period.name());
+                                }
+                            });
+                        if ui.small_button("Remove").clicked() {
+                            to_remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = to_remove {
+                    self.config.budgets.remove(index);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        self.config.budgets.push(Budget {
+                            tag: self.tag.trim().to_string(),
+                            hours: 10.0,
+                            period: BudgetPeriod::Week,
+                        });
+                    }
+                    if ui.button("Save").clicked() {
+                        self.save_config();
+                    }
+                });
+            });
+        self.show_budget_window = open;
+    }
+
+    /// Renders the billing window with the hourly rates, the rounding rule and the report export
+    fn render_billing_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_billing_window;
+        egui::Window::new("Billing")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let billing = &mut self.config.billing;
+                let mut to_remove = None;
+                egui::Grid::new("hourly_rates").show(ui, |ui| {
+                    for header in ["Tag", "Hourly rate", ""] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for (index, rate) in billing.rates.iter_mut().enumerate() {
+                        ui.add(egui::TextEdit::singleline(&mut rate.tag).desired_width(90.0));
+                        ui.add(egui::DragValue::new(&mut rate.rate).range(0.0..=10_000.0));
+                        if ui.small_button("Remove").clicked() {
+                            to_remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = to_remove {
+                    billing.rates.remove(index);
+                }
+                if ui.button("Add rate").clicked() {
+                    billing.rates.push(HourlyRate {
+                        tag: self.tag.trim().to_string(),
+                        rate: 0.0,
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Currency");
+                    ui.add(egui::TextEdit::singleline(&mut billing.currency).desired_width(40.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Round");
+                    egui::ComboBox::from_id_salt("billing_rounding")
+                        .selected_text(billing.rounding.name())
+                        .show_ui(ui, |ui| {
+                            for rounding in [
+                                BillingRounding::PerPomodoro,
+                                BillingRounding::PerQuarterHour,
+                                BillingRounding::PerDay,
+                            ] {
+                                ui.selectable_value(&mut billing.rounding, rounding, rounding.name());
+                            }
+                        });
+                });
+                if ui.button("Save settings").clicked() {
+                    self.save_config();
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.add(egui::TextEdit::singleline(&mut self.billing_from).desired_width(80.0));
+                    ui.label("To");
+                    ui.add(egui::TextEdit::singleline(&mut self.billing_to).desired_width(80.0));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Export CSV…").clicked() {
+                        self.export_billing_with_dialog(true);
+                    }
+                    if ui.button("Export Markdown…").clicked() {
+                        self.export_billing_with_dialog(false);
+                    }
+                });
+            });
+        self.show_billing_window = open;
+    }
+
+    /// Asks for a destination file and writes the billing report for the selected range to it
+    fn export_billing_with_dialog(&mut self, csv: bool) {
+        let range = match DateRange::parse(&self.billing_from, &self.billing_to) {
+            Ok(range) => range,
+            Err(err) => {
+                self.status_message = Some(err);
+                return;
+            }
+        };
+        let (name, extension) = if csv { ("CSV", "csv") } else { ("Markdown", "md") };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(name, &[extension])
+            .set_file_name(format!("billable-hours.{extension}"))
+            .save_file()
+        else {
+            return;
+        };
+        let lines = billing::billing_lines(self.history.records(), &range, &self.config.billing);
+        let mut text = Vec::new();
+        if csv {
+            // Writing into memory cannot fail.
+            let _ = billing::write_billing_csv(&mut text, &lines);
+        } else {
+            text = billing::billing_markdown(&lines, &range, &self.config.billing).into_bytes();
+        }
+        self.status_message = Some(match std::fs::write(&path, text) {
+            Ok(()) => format!("Billing report saved to {}", path.display()),
+            Err(err) => format!("Saving billing report failed: {err}"),
+        });
+    }
+
+    /// Renders the list of git repositories whose commits are shown in the history and reports
+    fn render_repositories_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_repositories_window;
+        egui::Window::new("Git repositories")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut to_remove = None;
+                for (index, repository) in self.config.git_repositories.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(repository.display().to_string());
+                        if ui.small_button("Remove").clicked() {
+                            to_remove = Some(index);
+                        }
+                    });
+                }
+                let mut changed = false;
+                if let Some(index) = to_remove {
+                    self.config.git_repositories.remove(index);
+                    changed = true;
+                }
+                if ui.button("Add…").clicked()
+                    && let Some(path) = rfd::FileDialog::new().pick_folder()
+                {
+                    self.config.git_repositories.push(path);
+                    changed = true;
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Author email");
+                    let mut author = self.config.git_author.clone().unwrap_or_default();
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut author)
+                            .hint_text("git user.email")
+                            .desired_width(180.0),
+                    );
+                    if response.changed() {
+                        let author = author.trim();
+                        self.config.git_author = (!author.is_empty()).then(|| author.to_string());
+                    }
+                    changed |= response.lost_focus();
+                });
+                if changed {
+                    self.save_config();
+                }
+            });
+        self.show_repositories_window = open;
+    }
+
+    /// Renders the sound slots, one row per event with its file, volume and a preview
+    fn render_sounds_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sounds_window;
+        egui::Window::new("Sounds")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut save = false;
+                egui::Grid::new("sound_slots").striped(true).show(ui, |ui| {
+                    for event in SoundEvent::ALL {
+                        let SoundSlot { mut enabled, file, mut volume } = self.config.sound_slot(event);
+                        let enabled_response = ui.checkbox(&mut enabled, event.name());
+                        let name = self.event_sounds.get(&event).map_or("Default", |sound| sound.name());
+                        ui.label(name);
+                        if ui.button("Choose…").clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Audio", &sound::SOUND_EXTENSIONS)
+                                .pick_file()
+                        {
+                            self.set_event_sound_file(event, Some(path));
+                        }
+                        if ui.add_enabled(file.is_some(), egui::Button::new("Use default")).clicked() {
+                            self.set_event_sound_file(event, None);
+                        }
+                        let volume_response = ui.add(egui::Slider::new(&mut volume, 0.0..=1.0).text("volume"));
+                        if enabled_response.changed() || volume_response.changed() {
+                            self.set_sound_slot(event, enabled, volume);
+                        }
+                        save |= enabled_response.changed()
+                            || volume_response.drag_stopped()
+                            || (volume_response.changed() && !volume_response.dragged());
+                        if ui.button("Preview").clicked() {
+                            let sound = self.event_sounds.get(&event).unwrap_or(self.default_event_sound(event)).clone();
+                            self.play_notification(&sound, volume);
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.label("The last pomodoro before the long break sounds like the others unless enabled.");
+                ui.separator();
+                let warning = &mut self.config.warning;
+                save |= ui.checkbox(&mut warning.enabled, "Warn before a phase ends").changed();
+                ui.add_enabled_ui(warning.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        save |= ui
+                            .add(egui::DragValue::new(&mut warning.minutes).range(0.5..=30.0).speed(0.5))
+                            .changed();
+                        ui.label("minutes before the end of");
+                        save |= ui.checkbox(&mut warning.pomodoros, "pomodoros").changed();
+                        save |= ui.checkbox(&mut warning.breaks, "breaks").changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Count down the last");
+                        save |= ui.add(egui::DragValue::new(&mut warning.countdown_seconds).range(0..=60)).changed();
+                        ui.label("seconds (0 for none)");
+                    });
+                });
+                ui.separator();
+                let alarm = &mut self.config.alarm;
+                save |= ui.checkbox(&mut alarm.enabled, "Repeat the end sound louder until acknowledged").changed();
+                ui.add_enabled_ui(alarm.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Every");
+                        save |= ui.add(egui::DragValue::new(&mut alarm.interval_seconds).range(2..=120)).changed();
+                        ui.label("seconds for at most");
+                        save |= ui.add(egui::DragValue::new(&mut alarm.max_minutes).range(1..=60)).changed();
+                        ui.label("minutes");
+                    });
+                });
+                if let Some(error) = &self.sound_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                if save {
+                    self.save_config();
+                }
+            });
+        self.show_sounds_window = open;
+    }
+
+    /// Renders the ambient sound layers with their volumes and the volume kept during pauses and breaks
+    fn render_ambient_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_ambient_window;
+        egui::Window::new("Ambient sound")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let ambient = &mut self.config.ambient;
+                let mut save = ui.checkbox(&mut ambient.enabled, "Play during pomodoros").changed();
+                ui.horizontal(|ui| {
+                    ui.label("During pauses and breaks");
+                    let response = ui.add(egui::Slider::new(&mut ambient.duck_volume, 0.0..=1.0));
+                    save |= response.drag_stopped() || (response.changed() && !response.dragged());
+                    if ambient.duck_volume == 0.0 {
+                        ui.label("(stopped)");
+                    }
+                });
+                ui.separator();
+                let mut to_remove = None;
+                egui::Grid::new("ambient_layers").show(ui, |ui| {
+                    for (index, layer) in ambient.layers.iter_mut().enumerate() {
+                        ui.label(layer.source.name());
+                        let response = ui.add(egui::Slider::new(&mut layer.volume, 0.0..=1.0).text("volume"));
+                        save |= response.drag_stopped() || (response.changed() && !response.dragged());
+                        if ui.small_button("Remove").clicked() {
+                            to_remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = to_remove {
+                    ambient.layers.remove(index);
+                    save = true;
+                }
+                ui.horizontal(|ui| {
+                    for color in NoiseColor::ALL {
+                        if ui.button(format!("Add {}", color.name().to_lowercase())).clicked() {
+                            ambient.layers.push(AmbientLayer { source: AmbientSource::Noise(color), volume: 0.5 });
+                            save = true;
+                        }
+                    }
+                    if ui.button("Add file…").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Audio", &sound::SOUND_EXTENSIONS)
+                            .pick_file()
+                    {
+                        ambient.layers.push(AmbientLayer { source: AmbientSource::File(path), volume: 0.5 });
+                        save = true;
+                    }
+                });
+                ui.separator();
+                ui.strong("Ticking clock");
+                let ticking = &mut self.config.ticking;
+                save |= ui.checkbox(&mut ticking.enabled, "Tick during pomodoros").changed();
+                ui.horizontal(|ui| {
+                    ui.label("Volume");
+                    let response = ui.add(egui::Slider::new(&mut ticking.volume, 0.0..=1.0));
+                    save |= response.drag_stopped() || (response.changed() && !response.dragged());
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Only in the last");
+                    save |= ui.add(egui::DragValue::new(&mut ticking.last_minutes).range(0..=60)).changed();
+                    ui.label(if ticking.last_minutes == 0 { "minutes (0 ticks throughout)" } else { "minutes" });
+                });
+                save |= ui.checkbox(&mut ticking.accelerate, "Speed up in the final minute").changed();
+                let mut tick_file = None;
+                ui.horizontal(|ui| {
+                    let name = ticking.file.as_ref().map_or("Click".to_string(), |path| AmbientSource::File(path.clone()).name());
+                    ui.label(format!("Tick: {name}"));
+                    if ui.button("Choose…").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Audio", &sound::SOUND_EXTENSIONS)
+                            .pick_file()
+                    {
+                        tick_file = Some(Some(path));
+                    }
+                    if ui.add_enabled(ticking.file.is_some(), egui::Button::new("Use click")).clicked() {
+                        tick_file = Some(None);
+                    }
+                });
+                if let Some(path) = tick_file {
+                    self.set_tick_file(path);
+                }
+                if let Some(error) = &self.sound_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                if save {
+                    self.save_config();
+                }
+            });
+        self.show_ambient_window = open;
+    }
+
+    /// Asks for a CSV file to import and opens the import window for it
+    fn open_import_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+            return;
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                self.import_file = Some((path, text));
+                self.import_preview = None;
+            }
+            Err(err) => self.status_message = Some(format!("Failed to read {}: {err}", path.display())),
+        }
+    }
+
+    /// Renders the import window with the column mapping, a dry-run preview and the rows that failed
+    fn render_import_window(&mut self, ctx: &egui::Context) {
+        let Some((path, text)) = &self.import_file else {
+            return;
+        };
+        let mut open = true;
+        let mut import = false;
+        egui::Window::new("Import CSV")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(path.display().to_string());
+                ui.horizontal(|ui| {
+                    ui.label("Preset");
+                    for preset in ImportPreset::ALL {
+                        if ui.button(preset.name()).clicked() {
+                            self.import_form = ImportForm::from_mapping(&preset.mapping());
+                            self.import_preview = None;
+                        }
+                    }
+                });
+                let form = &mut self.import_form;
+                egui::Grid::new("import_mapping").show(ui, |ui| {
+                    for (label, field) in [
+                        ("Start", &mut form.start),
+                        ("End", &mut form.end),
+                        ("Duration", &mut form.duration),
+                        ("Task", &mut form.label),
+                        ("Tag", &mut form.tag),
+                        ("Time format", &mut form.time_format),
+                    ] {
+                        ui.label(label);
+                        if ui.add(egui::TextEdit::singleline(field).desired_width(160.0)).changed() {
+                            self.import_preview = None;
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.small("Join columns with + to combine them, e.g. Start date+Start time");
+
+                if ui.button("Preview").clicked() {
+                    self.import_preview = Some(import::preview(text, &self.import_form.to_mapping(), self.history.records()));
+                }
+                match &self.import_preview {
+                    Some(Ok(preview)) => {
+                        ui.label(preview.report().lines().next().unwrap_or_default());
+                        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                            for record in &preview.records {
+                                ui.small(import::describe(record));
+                            }
+                            for (line, record) in &preview.duplicates {
+                                ui.small(format!("Duplicate, line {line}: {}", import::describe(record)));
+                            }
+                            for failure in &preview.failures {
+                                ui.colored_label(
+                                    ui.visuals().error_fg_color,
+                                    format!("Line {}: {}", failure.line, failure.message),
+                                );
+                            }
+                        });
+                        import = ui.add_enabled(!preview.records.is_empty(), egui::Button::new("Import")).clicked();
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+
+        if import && let Some(Ok(preview)) = &self.import_preview {
+            self.status_message = Some(match import::import(&mut self.history, preview) {
+                Ok(count) => format!("Imported {count} pomodoros"),
+                Err(err) => format!("Import failed: {err}"),
+            });
+            open = false;
+        }
+        if !open {
+            self.import_file = None;
+            self.import_preview = None;
+        }
+    }
+
+    /// Renders the window for turning the Taskwarrior integration on or off
+    fn render_taskwarrior_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_taskwarrior_window;
+        egui::Window::new("Taskwarrior")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Command");
+                    ui.add(egui::TextEdit::singleline(&mut self.taskwarrior_command).desired_width(150.0));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Enable").clicked() {
+                        let command = self.taskwarrior_command.trim();
+                        let command = if command.is_empty() { taskwarrior::DEFAULT_COMMAND } else { command };
+                        self.config.taskwarrior_command = Some(std::path::PathBuf::from(command));
+                        self.save_config();
+                        self.open_taskwarrior();
+                    }
+                    if ui.add_enabled(self.taskwarrior.is_some(), egui::Button::new("Disable")).clicked() {
+                        self.config.taskwarrior_command = None;
+                        self.save_config();
+                        self.open_taskwarrior();
+                    }
+                });
+            });
+        self.show_taskwarrior_window = open;
+    }
+
+    /// Writes the settings to the configuration file, reporting failures in the status line
+    fn save_config(&mut self) {
+        let Some(path) = &self.config_path else {
+            return;
+        };
+        self.status_message = Some(match self.config.save(path) {
+            Ok(()) => "Settings saved".to_string(),
+            Err(err) => format!("Saving settings failed: {err}"),
+        });
+    }
+
+    /// Renders the quiet hours schedules and what happens to sounds during them
+    fn render_quiet_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_quiet_window;
+        egui::Window::new("Quiet hours")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let quiet = &mut self.config.quiet;
+                let mut save = false;
+                let mut to_remove = None;
+                for (index, schedule) in quiet.schedules.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        for day in quiet::WEEKDAYS {
+                            let mut selected = schedule.days.contains(&day);
+                            if ui.toggle_value(&mut selected, &day.to_string()[..2]).changed() {
+                                if selected {
+                                    schedule.days.push(day);
+                                } else {
+                                    schedule.days.retain(|other| *other != day);
+                                }
+                                save = true;
+                            }
+                        }
+                        save |= time_input(ui, &mut schedule.start);
+                        ui.label("–");
+                        save |= time_input(ui, &mut schedule.end);
+                        if ui.small_button("Remove").clicked() {
+                            to_remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = to_remove {
+                    quiet.schedules.remove(index);
+                    save = true;
+                }
+                if ui.button("Add schedule").clicked() {
+                    quiet.schedules.push(quiet::QuietSchedule::default());
+                    save = true;
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Volume during quiet hours");
+                    let response = ui.add(egui::Slider::new(&mut quiet.volume, 0.0..=1.0));
+                    save |= response.drag_stopped() || (response.changed() && !response.dragged());
+                });
+                ui.label("When silenced, instead of a sound:");
+                save |= ui.checkbox(&mut quiet.flash, "Flash the window").changed();
+                save |= ui.checkbox(&mut quiet.request_attention, "Ask for attention").changed();
+                if save {
+                    self.save_config();
+                }
+            });
+        self.show_quiet_window = open;
+    }
+
+    /// Flashes the window and asks for attention in place of a sound silenced by quiet hours or
+    /// one that failed to play
+    fn render_visual_cue(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.focus_pending) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        if let Some(attention) = self.attention_pending.take() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(attention));
+        }
+        let Some(started) = self.flash_started else {
+            return;
+        };
+        let progress = started.elapsed().as_secs_f32() / FLASH_DURATION_SECONDS;
+        if progress >= 1.0 {
+            self.flash_started = None;
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("visual_cue")));
+        let alpha = ((1.0 - progress) * FLASH_MAX_ALPHA) as u8;
+        painter.rect_filled(ctx.screen_rect(), 0.0, Color32::from_rgba_unmultiplied(255, 255, 255, alpha));
+        ctx.request_repaint();
+    }
+
+    /// Renders the ringing alarm with the button that stops it
+    fn render_alarm_window(&mut self, ctx: &egui::Context) {
+        let Some(alarm) = &mut self.alarm else {
+            return;
+        };
+        if !alarm.attention_requested {
+            alarm.attention_requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Critical));
+        }
+        let elapsed = alarm.started.elapsed();
+        let mut acknowledged = false;
+        egui::Window::new("⏰ Time's up")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.heading(alarm.event.name());
+                ui.label(format!("Ringing for {} s", elapsed.as_secs()));
+                acknowledged = ui.button("Acknowledge").clicked();
+            });
+        if acknowledged {
+            self.acknowledge_alarm();
+        }
+    }
+
+    /// Renders the warning shown when a budget crosses 80% or 100%
+    fn render_budget_warning(&mut self, ctx: &egui::Context) {
+        let Some(warning) = &self.budget_warning else {
+            return;
+        };
+        let mut dismissed = false;
+        egui::Window::new("Budget warning")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 40.0))
+            .show(ctx, |ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+                dismissed = ui.button("OK").clicked();
+            });
+        if dismissed {
+            self.dismiss_budget_warning();
+        }
+    }
+
     /// Renders the status information and timer display
     fn render_status_and_timer(&self, ui: &mut egui::Ui) {
         ui.label(format!("Current Phase: {:?}", self.current_phase));
         ui.label(format!("Current Cycle: {} / {}", self.current_cycle, self.cycles));
+        if let Some(message) = &self.status_message {
+            ui.small(message);
+        }
+        if let Some(error) = &self.audio_error {
+            ui.colored_label(ui.visuals().warn_fg_color, "⚠ Sound unavailable, alerting visually")
+                .on_hover_text(error);
+        }
 
         let desired_size = Vec2::splat(TIMER_DISPLAY_SIZE);
         let (response_val, _rect) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
         self.draw_doughnut_timer(ui, response_val);
+
+        if self.timer_state != TimerState::Stopped
+            && self.current_phase == TimerPhase::Pomodoro
+            && !self.intention.trim().is_empty()
+        {
+            ui.vertical_centered(|ui| {
+                ui.label(format!("🎯 {}", self.intention.trim()));
+            });
+        }
+        if self.timer_state != TimerState::Stopped
+            && self.current_phase == TimerPhase::Pomodoro
+            && let Some(budget) = self.current_budget()
+        {
+            let used_min = budget.used_min(self.history.records(), chrono::Local::now().date_naive())
+                + self.get_spent_time_minutes().max(0.0);
+            let progress = if budget.limit_min() > 0.0 { used_min / budget.limit_min() } else { 1.0 };
+            ui.add(egui::ProgressBar::new(progress.min(1.0)).text(budget.progress_text(used_min)));
+        }
+    }
+
+    /// Renders the "What will I do?" prompt shown when a pomodoro is started
+    fn render_intention_prompt(&mut self, ctx: &egui::Context) {
+        if !self.show_intention_prompt {
+            return;
+        }
+        egui::Window::new("What will I do?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                let response = ui.text_edit_singleline(&mut self.intention);
+                if std::mem::take(&mut self.focus_intention) {
+                    response.request_focus();
+                }
+                let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                ui.horizontal(|ui| {
+                    if ui.button("OK").clicked() || submitted {
+                        self.show_intention_prompt = false;
+                    }
+                    if ui.button("Skip").clicked() {
+                        self.intention.clear();
+                        self.show_intention_prompt = false;
+                    }
+                });
+            });
+    }
+
+    /// Renders the "How did it go?" prompt shown after a pomodoro is completed
+    fn render_reflection_prompt(&mut self, ctx: &egui::Context) {
+        if self.pending_reflection.is_none() {
+            return;
+        }
+        egui::Window::new("How did it go?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Focus");
+                    for rating in 1..=MAX_FOCUS_RATING {
+                        ui.selectable_value(&mut self.reflection_rating, rating, rating.to_string());
+                    }
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.reflection_note)
+                        .hint_text("Notes")
+                        .desired_rows(3),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        let note = std::mem::take(&mut self.reflection_note);
+                        self.submit_reflection(self.reflection_rating, &note);
+                    }
+                    if ui.button("Skip").clicked() {
+                        self.skip_reflection();
+                    }
+                });
+            });
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_phase_warnings();
+        self.check_budget_progress();
         self.handle_timer_completion();
+        self.handle_alarm();
+        self.handle_audio_statuses();
+        self.sync_ambient();
+        self.sync_ticking();
+        self.refresh_external_files();
+        self.receive_taskwarrior_events();
+        self.commit_loader.configure(&self.config.git_repositories, self.config.git_author.as_deref());
         ctx.request_repaint_after(std::time::Duration::from_millis(REPAINT_INTERVAL_MS));
         self.update_window_icon(ctx);
 
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            self.render_menu_bar(ui);
+        });
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("My Pomodoro");
-            self.render_settings_ui(ui);
-            ui.separator();
-            self.render_control_buttons(ui);
-            self.render_status_and_timer(ui);
+            // The window has a fixed size, so everything below the controls must be reachable by scrolling.
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("My Pomodoro");
+                self.render_settings_ui(ui);
+                ui.separator();
+                self.render_control_buttons(ui);
+                self.render_task_ui(ui);
+                self.render_status_and_timer(ui);
+            });
         });
+        self.render_export_window(ctx);
+        self.render_report_window(ctx);
+        self.render_intention_prompt(ctx);
+        self.render_reflection_prompt(ctx);
+        self.render_budget_window(ctx);
+        self.render_budget_warning(ctx);
+        self.render_alarm_window(ctx);
+        self.render_quiet_window(ctx);
+        self.render_billing_window(ctx);
+        self.render_repositories_window(ctx);
+        self.render_sounds_window(ctx);
+        self.render_ambient_window(ctx);
+        self.render_taskwarrior_window(ctx);
+        self.render_import_window(ctx);
+        if self.history_editor.open {
+            self.history_editor.show(ctx, &mut self.history, &mut self.commit_loader);
+        }
+        self.render_visual_cue(ctx);
+    }
+}
+
+/// Rings the terminal bell if the app was started from a terminal.
+fn ring_terminal_bell() {
+    use std::io::{IsTerminal, Write};
+    let mut stderr = std::io::stderr();
+    if stderr.is_terminal() {
+        let _ = stderr.write_all(b"\x07");
+        let _ = stderr.flush();
+    }
+}
+
+/// Edits `time` as hours and minutes, returning whether it changed.
+fn time_input(ui: &mut egui::Ui, time: &mut chrono::NaiveTime) -> bool {
+    use chrono::Timelike;
+    let mut hour = time.hour();
+    let mut minute = time.minute();
+    let mut changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+    ui.label(":");
+    changed |= ui
+        .add(egui::DragValue::new(&mut minute).range(0..=59).custom_formatter(|value, _| format!("{value:02}")))
+        .changed();
+    if changed && let Some(new_time) = chrono::NaiveTime::from_hms_opt(hour, minute, 0) {
+        *time = new_time;
     }
+    changed
 }
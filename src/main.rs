@@ -2,18 +2,61 @@
 
 use egui::IconData;
 use mypomodoro::MyApp;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::config::Config;
+use mypomodoro::history::History;
 use eframe::egui;
 
 fn main() -> eframe::Result {
     env_logger::init();
 
-    let app = MyApp::default();
+    let command = cli::parse_args(std::env::args().skip(1));
+    if command != Ok(Command::Gui) {
+        attach_console();
+    }
+    let command = match command {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let opened = if command.writes_history() { History::open_default() } else { History::open_default_read_only() };
+    let mut history = match opened {
+        Ok(history) => history,
+        Err(err) => {
+            eprintln!("Failed to open history: {err}");
+            History::default()
+        }
+    };
+
+    let mut config_path = Config::default_path();
+    let config = match config_path.as_deref().map(Config::load).transpose() {
+        Ok(config) => config.unwrap_or_default(),
+        Err(err) => {
+            // Keep the unreadable file instead of overwriting it with the defaults.
+            eprintln!("Failed to read settings: {err}");
+            config_path = None;
+            Config::default()
+        }
+    };
+
+    if command != Command::Gui {
+        if let Err(err) = cli::run(&command, &mut history, &config) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let app = MyApp::with_history(history).with_config(config, config_path);
     // let icon_size = 64; // Standard icon size
     // let initial_icon = app.render_icon_data(icon_size);
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([265.0, 380.0])
+            .with_inner_size([265.0, 460.0])
             .with_resizable(false)
             .with_always_on_top()
             .with_icon(IconData::default()),
@@ -27,3 +70,21 @@ fn main() -> eframe::Result {
         }),
     )
 }
+
+/// Attaches to the console of the terminal the program was started from, if any. Release builds on
+/// Windows are GUI programs without a console, so the output of commands would be lost otherwise.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when started without a console, e.g. from Explorer, where there is nowhere to print to anyway.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
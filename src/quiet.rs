@@ -0,0 +1,76 @@
+//! Quiet hours: weekly schedules during which sounds are softened or replaced by visual cues.
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Length of the manual "quiet for 1 hour" override.
+pub const OVERRIDE_DURATION: Duration = Duration::hours(1);
+
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// A time range on some weekdays. A range ending before it starts runs past midnight,
+/// e.g. 22:00–07:00 on Friday covers Friday night and Saturday morning.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct QuietSchedule {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Default for QuietSchedule {
+    fn default() -> Self {
+        Self {
+            days: WEEKDAYS.to_vec(),
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+impl QuietSchedule {
+    pub fn contains(&self, time: NaiveDateTime) -> bool {
+        let (day, clock) = (time.weekday(), time.time());
+        if self.start <= self.end {
+            return self.days.contains(&day) && clock >= self.start && clock < self.end;
+        }
+        (self.days.contains(&day) && clock >= self.start) || (self.days.contains(&day.pred()) && clock < self.end)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietConfig {
+    pub schedules: Vec<QuietSchedule>,
+    /// Fraction of the volume kept during quiet hours; 0 replaces sounds with visual cues.
+    pub volume: f32,
+    /// Flashes the window instead of playing a silenced sound.
+    pub flash: bool,
+    /// Asks the system for attention instead of playing a silenced sound.
+    pub request_attention: bool,
+}
+
+impl Default for QuietConfig {
+    fn default() -> Self {
+        Self {
+            schedules: Vec::new(),
+            volume: 0.0,
+            flash: true,
+            request_attention: true,
+        }
+    }
+}
+
+impl QuietConfig {
+    /// Returns whether the local `time` falls in any schedule.
+    pub fn is_quiet_at(&self, time: NaiveDateTime) -> bool {
+        self.schedules.iter().any(|schedule| schedule.contains(time))
+    }
+}
@@ -0,0 +1,220 @@
+//! Markdown summaries of the recorded history for a day or a week.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+
+use crate::TimerPhase;
+use crate::commits::{self, Commit};
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+const UNNAMED: &str = "(none)";
+
+/// The span of days a report covers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReportPeriod {
+    Day(NaiveDate),
+    /// The Monday-to-Sunday week containing the date.
+    Week(NaiveDate),
+}
+
+impl ReportPeriod {
+    pub fn range(&self) -> DateRange {
+        match *self {
+            ReportPeriod::Day(date) => DateRange::new(Some(date), Some(date)),
+            ReportPeriod::Week(date) => {
+                let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                DateRange::new(Some(monday), Some(monday + Duration::days(6)))
+            }
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            ReportPeriod::Day(date) => format!("Pomodoro report for {}", date.format("%A, %Y-%m-%d")),
+            ReportPeriod::Week(_) => {
+                let range = self.range();
+                format!(
+                    "Pomodoro report for the week {} to {}",
+                    range.from.unwrap_or_default(),
+                    range.to.unwrap_or_default()
+                )
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    pomodoros: usize,
+    focus_min: f32,
+}
+
+impl Tally {
+    fn add(&mut self, record: &PhaseRecord) {
+        self.pomodoros += 1;
+        self.focus_min += record.actual_min;
+    }
+}
+
+/// Formats minutes as e.g. `25 min` or `2 h 05 min`.
+pub fn format_minutes(minutes: f32) -> String {
+    let total = minutes.round().max(0.0) as u32;
+    if total < 60 {
+        format!("{total} min")
+    } else {
+        format!("{} h {:02} min", total / 60, total % 60)
+    }
+}
+
+/// Builds a Markdown report of the pomodoros recorded within `period`.
+pub fn markdown_report(records: &[PhaseRecord], period: &ReportPeriod) -> String {
+    markdown_report_with_commits(records, &[], period)
+}
+
+/// Builds a Markdown report that also lists the commits authored during each pomodoro.
+pub fn markdown_report_with_commits(records: &[PhaseRecord], commits: &[Commit], period: &ReportPeriod) -> String {
+    let range = period.range();
+    let mut pomodoros: Vec<&PhaseRecord> = records
+        .iter()
+        .filter(|record| record.phase == TimerPhase::Pomodoro && range.contains_record(record))
+        .collect();
+    pomodoros.sort_by_key(|record| record.start);
+
+    let completed: Vec<&PhaseRecord> = pomodoros
+        .iter()
+        .copied()
+        .filter(|record| record.outcome == PhaseOutcome::Completed)
+        .collect();
+    let voided = pomodoros.iter().filter(|record| record.outcome == PhaseOutcome::Voided).count();
+    let interruptions: u32 = pomodoros.iter().map(|record| record.interruptions).sum();
+    let focus_min: f32 = completed.iter().map(|record| record.actual_min).sum();
+    let ratings: Vec<u8> = pomodoros.iter().filter_map(|record| record.focus_rating).collect();
+
+    let mut lines = vec![
+        format!("# {}", period.title()),
+        String::new(),
+        format!("- Completed pomodoros: {}", completed.len()),
+        format!("- Focus time: {}", format_minutes(focus_min)),
+        format!("- Interruptions: {interruptions}"),
+        format!("- Voided pomodoros: {voided}"),
+    ];
+    if !ratings.is_empty() {
+        let average = ratings.iter().map(|&rating| rating as f32).sum::<f32>() / ratings.len() as f32;
+        lines.push(format!("- Average focus: {average:.1} / 5"));
+    }
+
+    if !completed.is_empty() {
+        push_table(&mut lines, "Task", tally_by(&completed, |record| &record.task));
+        push_table(&mut lines, "Tag", tally_by(&completed, |record| &record.tag));
+        if matches!(period, ReportPeriod::Week(_)) {
+            push_table(&mut lines, "Day", tally_by_day(&completed));
+        }
+    }
+
+    let journal: Vec<&&PhaseRecord> = pomodoros.iter().filter(|record| has_journal_entry(record)).collect();
+    if !journal.is_empty() {
+        lines.extend([String::new(), "## Journal".to_string(), String::new()]);
+        for record in journal {
+            lines.push(journal_line(record, period));
+        }
+    }
+
+    let shipped: Vec<(&PhaseRecord, Vec<&Commit>)> = pomodoros
+        .iter()
+        .map(|&record| (record, commits::commits_during(commits, record)))
+        .filter(|(_, commits)| !commits.is_empty())
+        .collect();
+    if !shipped.is_empty() {
+        lines.extend([String::new(), "## Commits".to_string(), String::new()]);
+        for (record, commits) in shipped {
+            lines.push(commits_heading(record, period));
+            for commit in commits {
+                lines.push(format!("  - `{}` {} ({})", commit.short_hash(), commit.summary, commit.repository));
+            }
+        }
+    }
+
+    let mut report = lines.join("\n");
+    report.push('\n');
+    report
+}
+
+fn has_journal_entry(record: &PhaseRecord) -> bool {
+    !record.intention.trim().is_empty() || record.focus_rating.is_some() || !record.note.trim().is_empty()
+}
+
+/// Formats e.g. `- 10:00 (Report): Draft the intro → focus 4/5 — Finished the intro`.
+fn journal_line(record: &PhaseRecord, period: &ReportPeriod) -> String {
+    let start = record.start.with_timezone(&Local);
+    let time = match period {
+        ReportPeriod::Day(_) => start.format("%H:%M").to_string(),
+        ReportPeriod::Week(_) => start.format("%a %H:%M").to_string(),
+    };
+    let mut line = format!("- {time}");
+    if !record.task.is_empty() {
+        line.push_str(&format!(" ({})", record.task));
+    }
+    line.push(':');
+    if !record.intention.trim().is_empty() {
+        line.push_str(&format!(" {}", record.intention.trim()));
+    }
+    if let Some(rating) = record.focus_rating {
+        line.push_str(&format!(" → focus {rating}/5"));
+    }
+    if !record.note.trim().is_empty() {
+        line.push_str(&format!(" — {}", record.note.trim().replace('\n', " ")));
+    }
+    line
+}
+
+/// Formats e.g. `- 10:00–10:25 (Report)`.
+fn commits_heading(record: &PhaseRecord, period: &ReportPeriod) -> String {
+    let start = record.start.with_timezone(&Local);
+    let start = match period {
+        ReportPeriod::Day(_) => start.format("%H:%M").to_string(),
+        ReportPeriod::Week(_) => start.format("%a %H:%M").to_string(),
+    };
+    let mut line = format!("- {start}–{}", record.end.with_timezone(&Local).format("%H:%M"));
+    if !record.task.is_empty() {
+        line.push_str(&format!(" ({})", record.task));
+    }
+    line
+}
+
+fn tally_by<'a>(records: &[&'a PhaseRecord], key: impl Fn(&'a PhaseRecord) -> &'a String) -> BTreeMap<String, Tally> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for &record in records {
+        let name = key(record).trim();
+        let name = if name.is_empty() { UNNAMED } else { name };
+        tallies.entry(name.to_string()).or_default().add(record);
+    }
+    tallies
+}
+
+fn tally_by_day(records: &[&PhaseRecord]) -> BTreeMap<String, Tally> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for &record in records {
+        let day = record.local_date().format("%Y-%m-%d %a").to_string();
+        tallies.entry(day).or_default().add(record);
+    }
+    tallies
+}
+
+fn push_table(lines: &mut Vec<String>, heading: &str, tallies: BTreeMap<String, Tally>) {
+    lines.extend([
+        String::new(),
+        format!("## By {}", heading.to_lowercase()),
+        String::new(),
+        format!("| {heading} | Pomodoros | Focus time |"),
+        "| --- | ---: | ---: |".to_string(),
+    ]);
+    for (name, tally) in tallies {
+        lines.push(format!(
+            "| {} | {} | {} |",
+            name.replace('|', "\\|"),
+            tally.pomodoros,
+            format_minutes(tally.focus_min)
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+//! Notification sounds: the embedded bell or an audio file chosen by the user.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rodio::Decoder;
+use rodio::decoder::LoopedDecoder;
+use serde::{Deserialize, Serialize};
+
+/// A decoded sound ready to be played.
+pub type SoundSource = Decoder<Cursor<Arc<[u8]>>>;
+
+const BELL_DATA: &[u8] = include_bytes!("../resources/bell.mp3");
+const BELL_NAME: &str = "Bell";
+const BEEP_NAME: &str = "Beep";
+const BEEP_SAMPLE_RATE: u32 = 44_100;
+/// File extensions offered in the file dialog. rodio detects the actual format from the contents.
+pub const SOUND_EXTENSIONS: [&str; 4] = ["wav", "ogg", "flac", "mp3"];
+pub const DEFAULT_VOLUME: f32 = 0.1;
+
+/// Timer transitions that can play a sound.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SoundEvent {
+    PomodoroEnd,
+    /// The end of the last pomodoro of a cycle, before the long break.
+    LastPomodoroEnd,
+    ShortBreakEnd,
+    LongBreakEnd,
+    Pause,
+    Resume,
+    Skip,
+    /// Some minutes before a phase ends.
+    Warning,
+    /// Each of the final seconds of a phase.
+    Countdown,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 9] = [
+        SoundEvent::PomodoroEnd,
+        SoundEvent::LastPomodoroEnd,
+        SoundEvent::ShortBreakEnd,
+        SoundEvent::LongBreakEnd,
+        SoundEvent::Pause,
+        SoundEvent::Resume,
+        SoundEvent::Skip,
+        SoundEvent::Warning,
+        SoundEvent::Countdown,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SoundEvent::PomodoroEnd => "Pomodoro end",
+            SoundEvent::LastPomodoroEnd => "Last pomodoro end",
+            SoundEvent::ShortBreakEnd => "Short break end",
+            SoundEvent::LongBreakEnd => "Long break end",
+            SoundEvent::Pause => "Pause",
+            SoundEvent::Resume => "Resume",
+            SoundEvent::Skip => "Skip",
+            SoundEvent::Warning => "Warning before the end",
+            SoundEvent::Countdown => "Countdown",
+        }
+    }
+}
+
+/// Master volumes applied on top of the volume of each sound.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volume {
+    /// Volume of the event sounds.
+    pub alert: f32,
+    /// Volume of background sounds.
+    pub ambient: f32,
+    /// Silences everything without changing the volumes.
+    pub muted: bool,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            alert: 1.0,
+            ambient: 0.5,
+            muted: false,
+        }
+    }
+}
+
+impl Volume {
+    /// Returns the volume to play an alert of `volume` at.
+    pub fn alert_volume(&self, volume: f32) -> f32 {
+        if self.muted { 0.0 } else { volume * self.alert }
+    }
+
+    /// Returns the volume to play a background sound of `volume` at.
+    pub fn ambient_volume(&self, volume: f32) -> f32 {
+        if self.muted { 0.0 } else { volume * self.ambient }
+    }
+}
+
+/// The sound played for one event.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSlot {
+    pub enabled: bool,
+    /// Audio file of this event, or `None` for the default sound.
+    pub file: Option<PathBuf>,
+    pub volume: f32,
+}
+
+impl Default for SoundSlot {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            file: None,
+            volume: DEFAULT_VOLUME,
+        }
+    }
+}
+
+impl SoundSlot {
+    /// Returns the slot used until the user changes it. Only the ends of phases play a sound at first,
+    /// and the last pomodoro of a cycle sounds like any other. The warnings, which have their own
+    /// switch, play softer.
+    pub fn default_for(event: SoundEvent) -> Self {
+        match event {
+            SoundEvent::PomodoroEnd | SoundEvent::ShortBreakEnd | SoundEvent::LongBreakEnd => Self::default(),
+            SoundEvent::Warning | SoundEvent::Countdown => Self {
+                volume: DEFAULT_VOLUME / 2.0,
+                ..Self::default()
+            },
+            _ => Self {
+                enabled: false,
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// An encoded sound that has been checked to decode.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    name: String,
+    data: Arc<[u8]>,
+}
+
+/// Two sounds are equal when they share the data loaded from the same file.
+impl PartialEq for Sound {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Arc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl Default for Sound {
+    fn default() -> Self {
+        Self::bell()
+    }
+}
+
+impl Sound {
+    /// Returns the bell that is built into the app.
+    pub fn bell() -> Self {
+        Self {
+            name: BELL_NAME.to_string(),
+            data: Arc::from(BELL_DATA),
+        }
+    }
+
+    /// Returns a short synthesized beep.
+    pub fn beep() -> Self {
+        let len = BEEP_SAMPLE_RATE as usize * 80 / 1000;
+        let samples: Vec<i16> = (0..len)
+            .map(|index| {
+                let time = index as f32 / BEEP_SAMPLE_RATE as f32;
+                // Fade in and out over 5 ms to avoid clicks.
+                let envelope = (index.min(len - index) as f32 / (BEEP_SAMPLE_RATE as f32 * 0.005)).min(1.0);
+                ((time * 880.0 * std::f32::consts::TAU).sin() * envelope * i16::MAX as f32 * 0.8) as i16
+            })
+            .collect();
+        Self {
+            name: BEEP_NAME.to_string(),
+            data: Arc::from(wav_data(&samples, BEEP_SAMPLE_RATE)),
+        }
+    }
+
+    /// Reads a WAV, Ogg Vorbis, FLAC or MP3 file, failing if it cannot be decoded.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let sound = Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+            data: Arc::from(data),
+        };
+        sound
+            .source()
+            .map_err(|err| format!("Failed to decode {}: {err}", path.display()))?;
+        Ok(sound)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Decodes the sound for playing it through the audio service.
+    pub fn source(&self) -> Result<SoundSource, String> {
+        Decoder::new(Cursor::new(self.data.clone())).map_err(|err| err.to_string())
+    }
+
+    /// Decodes the sound to play it in an endless loop.
+    pub fn looped_source(&self) -> Result<LoopedDecoder<Cursor<Arc<[u8]>>>, String> {
+        Decoder::new_looped(Cursor::new(self.data.clone())).map_err(|err| err.to_string())
+    }
+}
+
+/// Encodes mono 16-bit samples as a WAV file.
+fn wav_data(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend(b"RIFF");
+    wav.extend((36 + data_len).to_le_bytes());
+    wav.extend(b"WAVEfmt ");
+    wav.extend(16u32.to_le_bytes());
+    wav.extend(1u16.to_le_bytes()); // PCM
+    wav.extend(1u16.to_le_bytes()); // mono
+    wav.extend(sample_rate.to_le_bytes());
+    wav.extend((sample_rate * 2).to_le_bytes());
+    wav.extend(2u16.to_le_bytes());
+    wav.extend(16u16.to_le_bytes());
+    wav.extend(b"data");
+    wav.extend(data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend(sample.to_le_bytes());
+    }
+    wav
+}
@@ -0,0 +1,157 @@
+//! Pomodoro statistics over a date range, printed as a table or JSON for scripts.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::TimerPhase;
+use crate::history::{DateRange, PhaseOutcome, PhaseRecord};
+use crate::report::format_minutes;
+
+const UNNAMED: &str = "(none)";
+
+/// Key that the rows of the statistics are grouped by.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StatsGrouping {
+    Tag,
+    Task,
+    Day,
+}
+
+impl StatsGrouping {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "tag" => Ok(StatsGrouping::Tag),
+            "task" => Ok(StatsGrouping::Task),
+            "day" => Ok(StatsGrouping::Day),
+            _ => Err(format!("Unknown grouping '{text}', expected tag, task or day")),
+        }
+    }
+
+    fn key(&self, record: &PhaseRecord) -> String {
+        let name = match self {
+            StatsGrouping::Tag => record.tag.trim(),
+            StatsGrouping::Task => record.task.trim(),
+            StatsGrouping::Day => return record.local_date().to_string(),
+        };
+        if name.is_empty() { UNNAMED } else { name }.to_string()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct StatsRow {
+    pub key: String,
+    pub pomodoros: usize,
+    pub focus_min: f32,
+    pub interruptions: u32,
+}
+
+impl StatsRow {
+    fn add(&mut self, record: &PhaseRecord) {
+        self.pomodoros += 1;
+        self.focus_min += record.actual_min;
+        self.interruptions += record.interruptions;
+    }
+}
+
+/// Totals of the pomodoros completed within a date range.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Stats {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub pomodoros: usize,
+    pub focus_min: f32,
+    pub interruptions: u32,
+    pub voided: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rows: Vec<StatsRow>,
+}
+
+impl Stats {
+    /// Computes the totals for `range`, with one row per group when `grouping` is set.
+    pub fn collect(records: &[PhaseRecord], range: &DateRange, grouping: Option<StatsGrouping>) -> Self {
+        let pomodoros = records
+            .iter()
+            .filter(|record| record.phase == TimerPhase::Pomodoro && range.contains_record(record));
+        let mut total = StatsRow::default();
+        let mut voided = 0;
+        let mut rows: BTreeMap<String, StatsRow> = BTreeMap::new();
+        for record in pomodoros {
+            match record.outcome {
+                PhaseOutcome::Completed => {}
+                PhaseOutcome::Voided => {
+                    voided += 1;
+                    continue;
+                }
+                PhaseOutcome::Skipped => continue,
+            }
+            total.add(record);
+            if let Some(grouping) = grouping {
+                let key = grouping.key(record);
+                rows.entry(key.clone()).or_insert_with(|| StatsRow { key, ..Default::default() }).add(record);
+            }
+        }
+
+        Self {
+            from: range.from,
+            to: range.to,
+            pomodoros: total.pomodoros,
+            focus_min: total.focus_min,
+            interruptions: total.interruptions,
+            voided,
+            rows: rows.into_values().collect(),
+        }
+    }
+
+    /// Formats the statistics as a plain-text table with a total line.
+    pub fn to_table(&self) -> String {
+        let mut rows: Vec<[String; 4]> = vec![[
+            String::new(),
+            "Pomodoros".to_string(),
+            "Focus time".to_string(),
+            "Interruptions".to_string(),
+        ]];
+        for row in &self.rows {
+            rows.push(table_row(&row.key, row.pomodoros, row.focus_min, row.interruptions));
+        }
+        rows.push(table_row("Total", self.pomodoros, self.focus_min, self.interruptions));
+
+        let mut widths = [0; 4];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut table = String::new();
+        for row in &rows {
+            let line = format!(
+                "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            );
+            table.push_str(line.trim_end());
+            table.push('\n');
+        }
+        if self.voided > 0 {
+            table.push_str(&format!("Voided pomodoros: {}\n", self.voided));
+        }
+        table
+    }
+}
+
+fn table_row(key: &str, pomodoros: usize, focus_min: f32, interruptions: u32) -> [String; 4] {
+    [
+        key.to_string(),
+        pomodoros.to_string(),
+        format_minutes(focus_min),
+        interruptions.to_string(),
+    ]
+}
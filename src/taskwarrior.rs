@@ -0,0 +1,174 @@
+//! Pending tasks from Taskwarrior, started, stopped and annotated through the `task` command.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use serde::Deserialize;
+
+pub const DEFAULT_COMMAND: &str = "task";
+/// Keeps `task` from asking questions on the terminal it does not have.
+const NON_INTERACTIVE: [&str; 2] = ["rc.confirmation=off", "rc.verbose=nothing"];
+
+/// A task as found in the output of `task export`. Fields the app does not use are ignored.
+#[derive(Debug, PartialEq, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TaskwarriorTask {
+    pub uuid: String,
+    pub description: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub urgency: f32,
+    pub status: String,
+}
+
+/// Runs the Taskwarrior command line program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Taskwarrior {
+    command: PathBuf,
+}
+
+impl Default for Taskwarrior {
+    fn default() -> Self {
+        Self::new(Path::new(DEFAULT_COMMAND))
+    }
+}
+
+impl Taskwarrior {
+    pub fn new(command: &Path) -> Self {
+        Self {
+            command: command.to_path_buf(),
+        }
+    }
+
+    pub fn command(&self) -> &Path {
+        &self.command
+    }
+
+    /// Returns the pending tasks, most urgent first.
+    pub fn pending_tasks(&self) -> io::Result<Vec<TaskwarriorTask>> {
+        let output = self.run(&["status:pending", "export"])?;
+        let mut tasks: Vec<TaskwarriorTask> = serde_json::from_slice(&output)?;
+        tasks.retain(|task| task.status == "pending" || task.status.is_empty());
+        tasks.sort_by(|a, b| b.urgency.total_cmp(&a.urgency));
+        Ok(tasks)
+    }
+
+    pub fn start(&self, uuid: &str) -> io::Result<()> {
+        self.run(&[uuid, "start"]).map(|_| ())
+    }
+
+    pub fn stop(&self, uuid: &str) -> io::Result<()> {
+        self.run(&[uuid, "stop"]).map(|_| ())
+    }
+
+    pub fn annotate(&self, uuid: &str, text: &str) -> io::Result<()> {
+        self.run(&[uuid, "annotate", text]).map(|_| ())
+    }
+
+    /// Runs the command with `args` and returns its standard output.
+    fn run(&self, args: &[&str]) -> io::Result<Vec<u8>> {
+        let output = Command::new(&self.command).args(NON_INTERACTIVE).args(args).output()?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(io::Error::other(format!("{} {} failed: {message}", self.command.display(), args.join(" "))));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// A command queued for the worker thread.
+enum Request {
+    Reload,
+    Start(String),
+    Stop(String),
+    Annotate(String, String),
+    /// Answers once every command queued before it has run.
+    Flush(Sender<()>),
+}
+
+/// What the worker thread reports back to the UI.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TaskwarriorEvent {
+    Tasks(Vec<TaskwarriorTask>),
+    Failed(String),
+}
+
+/// Runs Taskwarrior commands on a background thread, so that a slow `task` never blocks the UI.
+/// Commands run one at a time in the order they were queued.
+pub struct TaskwarriorWorker {
+    requests: Sender<Request>,
+    events: Receiver<TaskwarriorEvent>,
+}
+
+impl TaskwarriorWorker {
+    pub fn spawn(taskwarrior: Taskwarrior) -> Self {
+        let (requests, request_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        thread::spawn(move || {
+            // Ends when the worker is dropped and the queue has run.
+            for request in request_receiver {
+                let event = match request {
+                    Request::Reload => match taskwarrior.pending_tasks() {
+                        Ok(tasks) => TaskwarriorEvent::Tasks(tasks),
+                        Err(err) => TaskwarriorEvent::Failed(format!("Failed to read Taskwarrior tasks: {err}")),
+                    },
+                    Request::Start(uuid) => match taskwarrior.start(&uuid) {
+                        Ok(()) => continue,
+                        Err(err) => TaskwarriorEvent::Failed(format!("Failed to start Taskwarrior task: {err}")),
+                    },
+                    Request::Stop(uuid) => match taskwarrior.stop(&uuid) {
+                        Ok(()) => continue,
+                        Err(err) => TaskwarriorEvent::Failed(format!("Failed to stop Taskwarrior task: {err}")),
+                    },
+                    Request::Annotate(uuid, text) => match taskwarrior.annotate(&uuid, &text) {
+                        Ok(()) => continue,
+                        Err(err) => TaskwarriorEvent::Failed(format!("Failed to annotate Taskwarrior task: {err}")),
+                    },
+                    Request::Flush(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                let _ = event_sender.send(event);
+            }
+        });
+        Self { requests, events }
+    }
+
+    /// Reads the pending tasks again; they arrive as [`TaskwarriorEvent::Tasks`].
+    pub fn reload(&self) {
+        self.send(Request::Reload);
+    }
+
+    pub fn start(&self, uuid: &str) {
+        self.send(Request::Start(uuid.to_string()));
+    }
+
+    pub fn stop(&self, uuid: &str) {
+        self.send(Request::Stop(uuid.to_string()));
+    }
+
+    pub fn annotate(&self, uuid: &str, text: &str) {
+        self.send(Request::Annotate(uuid.to_string(), text.to_string()));
+    }
+
+    /// Returns the events reported since the last call, without waiting.
+    pub fn events(&self) -> Vec<TaskwarriorEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// Blocks until every command queued so far has run.
+    pub fn wait(&self) {
+        let (done, finished) = mpsc::channel();
+        self.send(Request::Flush(done));
+        let _ = finished.recv();
+    }
+
+    fn send(&self, request: Request) {
+        // The thread only stops when the worker is dropped, so sending cannot fail.
+        let _ = self.requests.send(request);
+    }
+}
@@ -0,0 +1,157 @@
+//! The ticking clock played during pomodoros.
+//!
+//! Ticks are placed by counting samples in a rodio source rather than by the UI's repaint loop,
+//! so they stay exactly one second apart and land on the whole seconds of the remaining time.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+use rodio::source::UniformSourceIterator;
+use serde::{Deserialize, Serialize};
+
+use crate::sound::Sound;
+
+pub const TICK_SAMPLE_RATE: u32 = 44_100;
+/// Longest tick taken from a file; a tick must end before the next one starts.
+const MAX_TICK_DURATION: Duration = Duration::from_millis(200);
+/// Remaining time below which an accelerating tick speeds up.
+pub const ACCELERATE_SECONDS: f32 = 60.0;
+/// Time between ticks at the very end of an accelerating pomodoro.
+pub const FASTEST_INTERVAL: f32 = 0.25;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TickConfig {
+    pub enabled: bool,
+    /// Audio file of one tick, or `None` for the synthesized click.
+    pub file: Option<PathBuf>,
+    pub volume: f32,
+    /// Ticks only in this many final minutes of a pomodoro; 0 ticks throughout.
+    pub last_minutes: u32,
+    /// Ticks faster during the final minute.
+    pub accelerate: bool,
+}
+
+impl Default for TickConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: None,
+            volume: 0.3,
+            last_minutes: 0,
+            accelerate: false,
+        }
+    }
+}
+
+impl TickConfig {
+    /// Returns the seconds until the next tick when `remaining` seconds are left, or `None` if no tick
+    /// should play at that time.
+    pub fn interval(&self, remaining: f32) -> Option<f32> {
+        if remaining <= 0.0 || (self.last_minutes > 0 && remaining > self.last_minutes as f32 * 60.0) {
+            return None;
+        }
+        if self.accelerate && remaining < ACCELERATE_SECONDS {
+            let progress = 1.0 - remaining / ACCELERATE_SECONDS;
+            return Some(1.0 - (1.0 - FASTEST_INTERVAL) * progress);
+        }
+        Some(1.0)
+    }
+}
+
+/// Returns the samples of the synthesized click: a short, decaying 1.6 kHz tone.
+pub fn synthesized_tick() -> Arc<[f32]> {
+    let len = TICK_SAMPLE_RATE as usize * 30 / 1000;
+    (0..len)
+        .map(|index| {
+            let time = index as f32 / TICK_SAMPLE_RATE as f32;
+            (time * 1600.0 * std::f32::consts::TAU).sin() * (-time * 200.0).exp() * 0.8
+        })
+        .collect()
+}
+
+/// Returns the first moments of `sound` as mono samples at the tick sample rate.
+pub fn tick_samples(sound: &Sound) -> Result<Arc<[f32]>, String> {
+    let source = sound.source()?;
+    let max_len = (TICK_SAMPLE_RATE as f32 * MAX_TICK_DURATION.as_secs_f32()) as usize;
+    Ok(UniformSourceIterator::<_, f32>::new(source, 1, TICK_SAMPLE_RATE).take(max_len).collect())
+}
+
+/// A mono source that plays `tick` as scheduled by a `TickConfig` until the pomodoro ends.
+pub struct TickSource {
+    tick: Arc<[f32]>,
+    config: TickConfig,
+    /// Samples left until the end of the pomodoro.
+    remaining: u64,
+    /// Samples until the next tick is due.
+    until_tick: u64,
+    /// Position in the tick being played, if any.
+    playing: Option<usize>,
+}
+
+impl TickSource {
+    /// Starts ticking with `remaining` time left in the pomodoro. The first tick is placed on the next
+    /// whole second of the remaining time.
+    pub fn new(tick: Arc<[f32]>, config: TickConfig, remaining: Duration) -> Self {
+        let remaining = (remaining.as_secs_f64() * TICK_SAMPLE_RATE as f64) as u64;
+        let until_tick = remaining % TICK_SAMPLE_RATE as u64;
+        Self { tick, config, remaining, until_tick, playing: None }
+    }
+
+    /// Returns a new source for the same pomodoro as this one, `elapsed` later.
+    pub fn after(&self, elapsed: Duration) -> Self {
+        let remaining = Duration::from_secs_f64(self.remaining as f64 / TICK_SAMPLE_RATE as f64).saturating_sub(elapsed);
+        Self::new(Arc::clone(&self.tick), self.config.clone(), remaining)
+    }
+}
+
+impl Iterator for TickSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.until_tick == 0 {
+            let remaining = self.remaining as f32 / TICK_SAMPLE_RATE as f32;
+            // Outside the ticking window, check again a second later.
+            let interval = self.config.interval(remaining);
+            if interval.is_some() {
+                self.playing = Some(0);
+            }
+            self.until_tick = (interval.unwrap_or(1.0) * TICK_SAMPLE_RATE as f32).round().max(1.0) as u64;
+        }
+        self.until_tick -= 1;
+        self.remaining -= 1;
+
+        let sample = match self.playing {
+            Some(position) => {
+                let sample = self.tick.get(position).copied();
+                self.playing = sample.map(|_| position + 1);
+                sample.unwrap_or(0.0)
+            }
+            None => 0.0,
+        };
+        Some(sample)
+    }
+}
+
+impl Source for TickSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        TICK_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
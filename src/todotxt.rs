@@ -0,0 +1,223 @@
+//! Tasks read from a todo.txt file, with completions written back to it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const PRIORITY_KEY: &str = "pri:";
+
+/// One line of a todo.txt file.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TodoTask {
+    /// The line as found in the file, used to find the task again when completing it.
+    pub line: String,
+    pub done: bool,
+    pub priority: Option<char>,
+    pub completion_date: Option<NaiveDate>,
+    pub creation_date: Option<NaiveDate>,
+    /// Everything after the priority and dates, including projects, contexts and `key:value` pairs.
+    pub description: String,
+    pub projects: Vec<String>,
+    pub contexts: Vec<String>,
+}
+
+impl TodoTask {
+    /// Parses a line in the todo.txt format, returning `None` for blank lines.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            return None;
+        }
+        let mut task = TodoTask { line: line.trim_end().to_string(), ..Default::default() };
+
+        if let Some(after) = rest.strip_prefix("x ") {
+            task.done = true;
+            rest = after.trim_start();
+        } else if let Some(priority) = parse_priority(rest) {
+            task.priority = Some(priority);
+            rest = rest[4..].trim_start();
+        }
+        if let Some((date, after)) = take_date(rest) {
+            if task.done {
+                task.completion_date = Some(date);
+                if let Some((created, after)) = take_date(after) {
+                    task.creation_date = Some(created);
+                    rest = after;
+                } else {
+                    rest = after;
+                }
+            } else {
+                task.creation_date = Some(date);
+                rest = after;
+            }
+        }
+
+        task.description = rest.to_string();
+        for word in rest.split_whitespace() {
+            if let Some(project) = word.strip_prefix('+').filter(|name| !name.is_empty()) {
+                task.projects.push(project.to_string());
+            } else if let Some(context) = word.strip_prefix('@').filter(|name| !name.is_empty()) {
+                task.contexts.push(context.to_string());
+            }
+        }
+        Some(task)
+    }
+
+    /// Returns the description without projects, contexts and `key:value` pairs.
+    pub fn title(&self) -> String {
+        self.description
+            .split_whitespace()
+            .filter(|word| !is_project_or_context(word) && !is_key_value(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the line marking this task as completed on `date`.
+    /// A priority is kept as a `pri:A` pair, as completed tasks have no priority.
+    pub fn completed_line(&self, date: NaiveDate) -> String {
+        let mut line = format!("x {}", date.format(DATE_FORMAT));
+        if let Some(created) = self.creation_date {
+            line.push_str(&format!(" {}", created.format(DATE_FORMAT)));
+        }
+        line.push(' ');
+        line.push_str(&self.description);
+        if let Some(priority) = self.priority {
+            line.push_str(&format!(" {PRIORITY_KEY}{priority}"));
+        }
+        line
+    }
+}
+
+fn parse_priority(text: &str) -> Option<char> {
+    let bytes = text.as_bytes();
+    if bytes.len() >= 4 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')' && bytes[3] == b' ' {
+        Some(bytes[1] as char)
+    } else {
+        None
+    }
+}
+
+fn take_date(text: &str) -> Option<(NaiveDate, &str)> {
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let date = NaiveDate::parse_from_str(word, DATE_FORMAT).ok()?;
+    Some((date, rest.trim_start()))
+}
+
+fn is_project_or_context(word: &str) -> bool {
+    word.len() > 1 && (word.starts_with('+') || word.starts_with('@'))
+}
+
+fn is_key_value(word: &str) -> bool {
+    // URLs such as https://example.com contain a colon too, but the value never starts with a slash in todo.txt pairs.
+    // Keys are words like `due` or `t`, so times such as 10:30 and words ending in a colon stay in the title.
+    word.split_once(':').is_some_and(|(key, value)| {
+        key.chars().any(|c| c.is_alphabetic())
+            && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            && !value.is_empty()
+            && !value.starts_with('/')
+    })
+}
+
+/// The tasks of a todo.txt file, re-read whenever the file changes on disk.
+#[derive(Debug, Default)]
+pub struct TodoList {
+    path: PathBuf,
+    tasks: Vec<TodoTask>,
+    modified: Option<SystemTime>,
+}
+
+impl TodoList {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut list = Self {
+            path: path.to_path_buf(),
+            ..Default::default()
+        };
+        list.reload()?;
+        Ok(list)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn tasks(&self) -> &[TodoTask] {
+        &self.tasks
+    }
+
+    /// Returns the tasks that are not done, by priority and then in file order.
+    pub fn open_tasks(&self) -> Vec<&TodoTask> {
+        let mut tasks: Vec<&TodoTask> = self.tasks.iter().filter(|task| !task.done).collect();
+        // Tasks without a priority sort after `(Z)`.
+        tasks.sort_by_key(|task| task.priority.unwrap_or('~'));
+        tasks
+    }
+
+    /// Returns the open tasks with `context`, or all open tasks for `None`.
+    pub fn open_tasks_in(&self, context: Option<&str>) -> Vec<&TodoTask> {
+        let mut tasks = self.open_tasks();
+        if let Some(context) = context {
+            tasks.retain(|task| task.contexts.iter().any(|other| other == context));
+        }
+        tasks
+    }
+
+    /// Returns the contexts of the open tasks, sorted and without duplicates.
+    pub fn contexts(&self) -> Vec<&str> {
+        let mut contexts: Vec<&str> = self
+            .tasks
+            .iter()
+            .filter(|task| !task.done)
+            .flat_map(|task| task.contexts.iter().map(String::as_str))
+            .collect();
+        contexts.sort_unstable();
+        contexts.dedup();
+        contexts
+    }
+
+    /// Re-reads the file if it was modified since it was last read, returning whether it was.
+    pub fn refresh(&mut self) -> io::Result<bool> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if Some(modified) == self.modified {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn reload(&mut self) -> io::Result<()> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        let text = fs::read_to_string(&self.path)?;
+        self.tasks = text.lines().filter_map(TodoTask::parse).collect();
+        self.modified = Some(modified);
+        Ok(())
+    }
+
+    /// Marks `task` as completed on `date` in the file. The file is read again first so that
+    /// edits made by other programs are kept.
+    pub fn complete(&mut self, task: &TodoTask, date: NaiveDate) -> io::Result<()> {
+        let text = fs::read_to_string(&self.path)?;
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let index = lines
+            .iter()
+            .position(|line| line.trim_end() == task.line)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("Task '{}' is no longer in the file", task.title())))?;
+        lines[index] = task.completed_line(date);
+
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let mut updated = lines.join(newline);
+        if text.ends_with('\n') {
+            updated.push_str(newline);
+        }
+        // Replace the file a symlink points to rather than the link itself, keeping its permissions.
+        let path = fs::canonicalize(&self.path)?;
+        let temp_path = path.with_extension("txt.tmp");
+        fs::write(&temp_path, updated)?;
+        fs::set_permissions(&temp_path, fs::metadata(&path)?.permissions())?;
+        fs::rename(&temp_path, &path)?;
+        self.reload()
+    }
+}
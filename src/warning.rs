@@ -0,0 +1,55 @@
+//! Heads-up alerts before a phase ends: a warning some minutes before and a countdown of beeps.
+
+use serde::{Deserialize, Serialize};
+
+use crate::TimerPhase;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarningConfig {
+    pub enabled: bool,
+    /// Minutes before the end of a phase at which the warning sounds.
+    pub minutes: f32,
+    pub pomodoros: bool,
+    pub breaks: bool,
+    /// Beeps once a second in this many final seconds; 0 turns the countdown off.
+    pub countdown_seconds: u32,
+}
+
+impl Default for WarningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            minutes: 2.0,
+            pomodoros: true,
+            breaks: true,
+            countdown_seconds: 0,
+        }
+    }
+}
+
+impl WarningConfig {
+    pub fn applies_to(&self, phase: TimerPhase) -> bool {
+        self.enabled
+            && match phase {
+                TimerPhase::Pomodoro => self.pomodoros,
+                TimerPhase::ShortBreak | TimerPhase::LongBreak => self.breaks,
+            }
+    }
+
+    /// Returns whether a phase of `duration_sec` with `remaining_sec` left is inside the warning period.
+    /// Phases no longer than the warning period never warn, as they would warn as soon as they start.
+    pub fn in_warning_period(&self, phase: TimerPhase, duration_sec: f32, remaining_sec: f32) -> bool {
+        let warning_sec = self.minutes * 60.0;
+        self.applies_to(phase) && warning_sec > 0.0 && duration_sec > warning_sec && remaining_sec > 0.0 && remaining_sec <= warning_sec
+    }
+
+    /// Returns the second of the countdown that `remaining_sec` falls in, counting down to 1.
+    pub fn countdown_second(&self, phase: TimerPhase, remaining_sec: f32) -> Option<u32> {
+        if !self.applies_to(phase) || remaining_sec <= 0.0 {
+            return None;
+        }
+        let second = remaining_sec.ceil() as u32;
+        (second <= self.countdown_seconds).then_some(second)
+    }
+}
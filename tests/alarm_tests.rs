@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use mypomodoro::alarm::{AlarmConfig, ESCALATION_STEPS, escalated_volume};
+use mypomodoro::config::Config;
+use mypomodoro::history::History;
+use mypomodoro::sound::SoundEvent;
+use mypomodoro::{MyApp, TimerPhase};
+
+#[test]
+fn test_repeats_stop_after_max_duration() {
+    let config = AlarmConfig { enabled: true, interval_seconds: 10, max_minutes: 1 };
+    assert_eq!(config.repeats_due(Duration::from_secs(9)), 0);
+    assert_eq!(config.repeats_due(Duration::from_secs(25)), 2);
+    assert_eq!(config.repeats_due(Duration::from_secs(60)), 6);
+    assert_eq!(config.repeats_due(Duration::from_secs(600)), 6);
+    assert!(!config.is_expired(Duration::from_secs(59)));
+    assert!(config.is_expired(Duration::from_secs(60)));
+}
+
+#[test]
+fn test_volume_escalates_to_full() {
+    assert_eq!(escalated_volume(0.2, 0), 0.2);
+    assert!(escalated_volume(0.2, 1) > 0.2);
+    assert_eq!(escalated_volume(0.2, ESCALATION_STEPS), 1.0);
+    assert_eq!(escalated_volume(0.2, ESCALATION_STEPS * 3), 1.0);
+}
+
+#[test]
+fn test_acknowledgement_is_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = History::open(dir.path(), "machine").unwrap();
+    let config = Config { alarm: AlarmConfig { enabled: true, ..Default::default() }, ..Default::default() };
+    let mut app = MyApp::with_history(history).with_config(config, None);
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    app.complete_phase();
+    let alarm = app.alarm().unwrap();
+    assert_eq!(alarm.event, SoundEvent::ShortBreakEnd);
+    let id = alarm.record_id.clone().unwrap();
+
+    std::thread::sleep(Duration::from_millis(20));
+    app.acknowledge_alarm();
+    assert!(app.alarm().is_none());
+    let acknowledged_s = app.history().get(&id).unwrap().alarm_acknowledged_s.unwrap();
+    assert!(acknowledged_s >= 0.02);
+
+    // Reopening the history keeps the acknowledgement time.
+    let reopened = History::open(dir.path(), "machine").unwrap();
+    assert_eq!(reopened.get(&id).unwrap().alarm_acknowledged_s, Some(acknowledged_s));
+}
+
+#[test]
+fn test_alarm_replaced_before_acknowledgement_is_recorded_as_missed() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = History::open(dir.path(), "machine").unwrap();
+    let config = Config { alarm: AlarmConfig { enabled: true, ..Default::default() }, ..Default::default() };
+    let mut app = MyApp::with_history(history).with_config(config, None);
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    app.complete_phase();
+    let missed = app.alarm().unwrap().record_id.clone().unwrap();
+    app.complete_phase();
+    let ringing = app.alarm().unwrap().record_id.clone().unwrap();
+    assert_ne!(missed, ringing);
+
+    let record = app.history().get(&missed).unwrap();
+    assert!(record.alarm_missed);
+    assert_eq!(record.alarm_acknowledged_s, None);
+    app.acknowledge_alarm();
+    assert!(!app.history().get(&ringing).unwrap().alarm_missed);
+}
+
+#[test]
+fn test_timer_actions_acknowledge_the_alarm() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = History::open(dir.path(), "machine").unwrap();
+    let config = Config { alarm: AlarmConfig { enabled: true, ..Default::default() }, ..Default::default() };
+    let mut app = MyApp::with_history(history).with_config(config, None);
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    app.complete_phase();
+    let skipped = app.alarm().unwrap().record_id.clone().unwrap();
+    app.skip_phase();
+    assert!(app.alarm().is_none());
+    assert!(app.history().get(&skipped).unwrap().alarm_acknowledged_s.is_some());
+
+    app.complete_phase();
+    let reset = app.alarm().unwrap().record_id.clone().unwrap();
+    app.reset_timer();
+    assert!(app.alarm().is_none());
+    assert!(app.history().get(&reset).unwrap().alarm_acknowledged_s.is_some());
+}
+
+#[test]
+fn test_expired_alarm_is_recorded_as_missed() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = History::open(dir.path(), "machine").unwrap();
+    let alarm = AlarmConfig { enabled: true, max_minutes: 0, ..Default::default() };
+    let mut app = MyApp::with_history(history).with_config(Config { alarm, ..Default::default() }, None);
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    app.complete_phase();
+    let id = app.alarm().unwrap().record_id.clone().unwrap();
+    app.handle_alarm();
+
+    assert!(app.alarm().is_none());
+    let record = app.history().get(&id).unwrap();
+    assert!(record.alarm_missed);
+    assert_eq!(record.alarm_acknowledged_s, None);
+}
+
+#[test]
+fn test_no_alarm_when_disabled() {
+    let mut app = MyApp::default();
+    app.begin_timer();
+    app.complete_phase();
+    assert!(app.alarm().is_none());
+}
@@ -0,0 +1,66 @@
+use mypomodoro::ambient::{AmbientConfig, AmbientLayer, AmbientSource, Noise, NoiseColor, approach};
+use mypomodoro::config::Config;
+use mypomodoro::sound::Volume;
+use mypomodoro::{MyApp, TimerPhase};
+
+/// Returns the mean absolute difference between neighbouring samples, which is lower for darker noise.
+fn roughness(color: NoiseColor) -> f32 {
+    let samples: Vec<f32> = Noise::new(color).take(44_100).collect();
+    assert!(samples.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+    assert!(samples.iter().any(|sample| sample.abs() > 0.01));
+    samples.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum::<f32>() / samples.len() as f32
+}
+
+#[test]
+fn test_noise_colors() {
+    let white = roughness(NoiseColor::White);
+    let pink = roughness(NoiseColor::Pink);
+    let brown = roughness(NoiseColor::Brown);
+    assert!(white > pink, "white {white} pink {pink}");
+    assert!(pink > brown, "pink {pink} brown {brown}");
+}
+
+#[test]
+fn test_approach() {
+    assert_eq!(approach(0.0, 1.0, 0.25), 0.25);
+    assert_eq!(approach(0.9, 1.0, 0.25), 1.0);
+    assert_eq!(approach(0.5, 0.0, 0.25), 0.25);
+    assert_eq!(approach(0.1, 0.0, 0.25), 0.0);
+}
+
+#[test]
+fn test_ambient_level_follows_timer() {
+    let ambient = AmbientConfig {
+        enabled: true,
+        layers: vec![AmbientLayer { source: AmbientSource::Noise(NoiseColor::Pink), volume: 0.8 }],
+        duck_volume: 0.25,
+    };
+    let config = Config { ambient, volume: Volume { ambient: 1.0, ..Default::default() }, ..Default::default() };
+    let mut app = MyApp::default().with_config(config, None);
+    assert_eq!(app.ambient_level(), 0.0);
+
+    app.begin_timer();
+    assert_eq!(app.ambient_level(), 1.0);
+    app.pause_timer();
+    assert_eq!(app.ambient_level(), 0.25);
+    app.resume_timer();
+    app.complete_phase();
+    assert_eq!(app.current_phase(), TimerPhase::ShortBreak);
+    assert_eq!(app.ambient_level(), 0.25);
+
+    app.toggle_mute();
+    assert_eq!(app.ambient_level(), 0.0);
+}
+
+#[test]
+fn test_ambient_config_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let mut config = Config::default();
+    assert!(!config.ambient.enabled);
+    config.ambient.layers.push(AmbientLayer { source: AmbientSource::File(dir.path().join("rain.ogg")), volume: 0.3 });
+    config.save(&path).unwrap();
+    let loaded = Config::load(&path).unwrap();
+    assert_eq!(loaded.ambient, config.ambient);
+    assert_eq!(loaded.ambient.layers[1].source.name(), "rain.ogg");
+}
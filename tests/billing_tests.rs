@@ -0,0 +1,136 @@
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::billing::{self, BillingConfig, BillingRounding, HourlyRate};
+use mypomodoro::cli::{self, Command};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+fn phase(day: u32, hour: u32, phase: TimerPhase, tag: &str, actual_min: f32) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(actual_min as i64),
+        phase,
+        planned_min: 25.0,
+        actual_min,
+        tag: tag.to_string(),
+        ..Default::default()
+    }
+}
+
+fn records() -> Vec<PhaseRecord> {
+    let mut voided = phase(15, 12, TimerPhase::Pomodoro, "acme", 20.0);
+    voided.outcome = PhaseOutcome::Voided;
+    vec![
+        phase(15, 9, TimerPhase::Pomodoro, "acme", 24.0),
+        phase(15, 10, TimerPhase::ShortBreak, "acme", 5.0),
+        phase(15, 11, TimerPhase::Pomodoro, "acme", 25.0),
+        voided,
+        phase(15, 13, TimerPhase::Pomodoro, "internal", 25.0),
+        phase(16, 9, TimerPhase::Pomodoro, "globex", 25.0),
+    ]
+}
+
+fn config(rounding: BillingRounding) -> BillingConfig {
+    BillingConfig {
+        rates: vec![
+            HourlyRate { tag: "acme".to_string(), rate: 90.0 },
+            HourlyRate { tag: "globex".to_string(), rate: 120.0 },
+        ],
+        rounding,
+        currency: "EUR".to_string(),
+    }
+}
+
+fn billed(rounding: BillingRounding) -> Vec<(String, u32)> {
+    billing::billing_lines(&records(), &DateRange::default(), &config(rounding))
+        .into_iter()
+        .map(|line| (line.tag, line.billed_min))
+        .collect()
+}
+
+#[test]
+fn test_billing_rounding_rules() {
+    assert_eq!(
+        billed(BillingRounding::PerPomodoro),
+        [("acme".to_string(), 50), ("globex".to_string(), 25)]
+    );
+    assert_eq!(
+        billed(BillingRounding::PerQuarterHour),
+        [("acme".to_string(), 60), ("globex".to_string(), 30)]
+    );
+    assert_eq!(
+        billed(BillingRounding::PerDay),
+        [("acme".to_string(), 60), ("globex".to_string(), 30)]
+    );
+}
+
+#[test]
+fn test_billing_uses_actual_minutes_and_exact_cents() {
+    let mut records = vec![
+        phase(15, 9, TimerPhase::Pomodoro, "acme", 10.0),
+        phase(15, 10, TimerPhase::Pomodoro, "acme", 30.0),
+    ];
+    let config = BillingConfig {
+        rates: vec![HourlyRate { tag: "acme".to_string(), rate: 33.33 }],
+        ..config(BillingRounding::PerPomodoro)
+    };
+    // A pomodoro ended early is billed as one pomodoro, one that overran as two.
+    let lines = billing::billing_lines(&records, &DateRange::default(), &config);
+    assert_eq!(lines[0].billed_min, 75);
+
+    // A year of pomodoros still adds up to the exact cent.
+    records = (0..2000).map(|_| phase(15, 9, TimerPhase::Pomodoro, "acme", 25.0)).collect();
+    let lines = billing::billing_lines(&records, &DateRange::default(), &config);
+    assert_eq!(lines[0].billed_min, 50_000);
+    assert_eq!(lines[0].amount_cents(), 2_777_500);
+}
+
+#[test]
+fn test_billing_excludes_breaks_voided_and_unrated() {
+    let lines = billing::billing_lines(&records(), &DateRange::default(), &config(BillingRounding::PerPomodoro));
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].date, NaiveDate::from_ymd_opt(2026, 10, 15).unwrap());
+    assert_eq!(lines[0].pomodoros, 2);
+    assert_eq!(lines[0].worked_min, 49.0);
+    assert_eq!(lines[0].amount_cents(), 7500);
+}
+
+#[test]
+fn test_billing_csv_and_markdown() {
+    let range = DateRange::new(NaiveDate::from_ymd_opt(2026, 10, 15), NaiveDate::from_ymd_opt(2026, 10, 16));
+    let config = config(BillingRounding::PerQuarterHour);
+    let lines = billing::billing_lines(&records(), &range, &config);
+
+    let mut csv = Vec::new();
+    billing::write_billing_csv(&mut csv, &lines).unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "date,tag,pomodoros,worked_hours,billed_hours,rate,amount\r\n\
+         2026-10-15,acme,2,0.82,1.00,90.00,90.00\r\n\
+         2026-10-16,globex,1,0.42,0.50,120.00,60.00\r\n"
+    );
+
+    let markdown = billing::billing_markdown(&lines, &range, &config);
+    assert!(markdown.starts_with("# Billable hours, 2026-10-15 to 2026-10-16\n"));
+    assert!(markdown.contains("Breaks and skipped or voided pomodoros are not billed."));
+    assert!(markdown.contains("| 2026-10-15 | acme | 2 | 49 min | 1 h 00 min | 90.00 EUR | 90.00 EUR |"));
+    assert!(markdown.contains("- **Total: 150.00 EUR**"));
+}
+
+#[test]
+fn test_parse_billing_args() {
+    let command = cli::parse_args(
+        ["billing", "--from", "2026-10-01", "--rounding", "15min", "--csv"].map(String::from),
+    );
+    assert_eq!(
+        command,
+        Ok(Command::Billing {
+            range: DateRange::new(NaiveDate::from_ymd_opt(2026, 10, 1), None),
+            rounding: Some(BillingRounding::PerQuarterHour),
+            csv: true,
+            output: None,
+        })
+    );
+    assert!(cli::parse_args(["billing", "--rounding", "hour"].map(String::from)).is_err());
+}
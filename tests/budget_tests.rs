@@ -0,0 +1,134 @@
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use mypomodoro::budget::{self, Budget, BudgetPeriod};
+use mypomodoro::config::Config;
+use mypomodoro::history::{DateRange, History, PhaseOutcome, PhaseRecord};
+use mypomodoro::{MyApp, TimerPhase};
+
+fn pomodoro(month: u32, day: u32, tag: &str) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, month, day, 9, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        tag: tag.to_string(),
+        ..Default::default()
+    }
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+#[test]
+fn test_budget_period_ranges() {
+    assert_eq!(
+        BudgetPeriod::Week.range(date(10, 15)),
+        DateRange::new(Some(date(10, 12)), Some(date(10, 18)))
+    );
+    assert_eq!(
+        BudgetPeriod::Month.range(date(2, 10)),
+        DateRange::new(Some(date(2, 1)), Some(date(2, 28)))
+    );
+}
+
+#[test]
+fn test_budget_used_minutes() {
+    let mut voided = pomodoro(10, 14, "migration");
+    voided.outcome = PhaseOutcome::Voided;
+    let records = vec![
+        pomodoro(10, 5, "migration"),
+        pomodoro(10, 13, "migration"),
+        pomodoro(10, 14, "other"),
+        voided,
+        pomodoro(10, 15, " migration "),
+    ];
+    let weekly = Budget { tag: "migration".to_string(), hours: 10.0, period: BudgetPeriod::Week };
+    let monthly = Budget { period: BudgetPeriod::Month, ..weekly.clone() };
+
+    assert_eq!(weekly.used_min(&records, date(10, 15)), 50.0);
+    assert_eq!(monthly.used_min(&records, date(10, 15)), 75.0);
+    assert_eq!(weekly.progress_text(390.0), "migration: 6 h 30 min of 10 h 00 min this week (65%)");
+}
+
+#[test]
+fn test_threshold_warnings() {
+    let budget = Budget { tag: "migration".to_string(), hours: 1.0, period: BudgetPeriod::Week };
+
+    assert_eq!(budget::threshold_warning(&budget, 25.0, 40.0), None);
+    assert!(budget::threshold_warning(&budget, 40.0, 50.0).unwrap().starts_with("80%"));
+    assert!(budget::threshold_warning(&budget, 50.0, 75.0).unwrap().ends_with("is used up"));
+    assert_eq!(budget::threshold_warning(&budget, 75.0, 100.0), None);
+    assert!(budget::find(std::slice::from_ref(&budget), "").is_none());
+}
+
+#[test]
+fn test_app_warns_on_exhausted_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut history = History::open(dir.path(), "desk").unwrap();
+    // Midnight of today is always in the current week, whatever the time the test runs at.
+    let start = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .unwrap()
+        .with_timezone(&Utc);
+    history
+        .append(PhaseRecord { start, end: start + chrono::Duration::minutes(25), ..pomodoro(10, 1, "migration") })
+        .unwrap();
+    let config = Config {
+        budgets: vec![Budget { tag: "migration".to_string(), hours: 0.25, period: BudgetPeriod::Week }],
+        ..Default::default()
+    };
+    let mut app = MyApp::with_history(history).with_config(config, None);
+
+    app.begin_timer();
+    assert_eq!(app.budget_warning(), None);
+    app.reset_timer();
+
+    app.set_tag("migration");
+    app.begin_timer();
+    assert!(app.budget_warning().unwrap().starts_with("Budget exhausted"));
+    app.dismiss_budget_warning();
+    assert_eq!(app.budget_warning(), None);
+}
+
+#[test]
+fn test_app_warns_when_running_pomodoro_crosses_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = Config {
+        budgets: vec![Budget { tag: "migration".to_string(), hours: 0.5, period: BudgetPeriod::Week }],
+        ..Default::default()
+    };
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap()).with_config(config, None);
+
+    app.set_tag("migration");
+    app.begin_timer();
+    app.update_budget_progress(0.0);
+    app.update_budget_progress(20.0);
+    assert_eq!(app.budget_warning(), None);
+
+    app.update_budget_progress(25.0);
+    assert!(app.budget_warning().unwrap().starts_with("80%"));
+    app.dismiss_budget_warning();
+    app.update_budget_progress(29.0);
+    assert_eq!(app.budget_warning(), None);
+    app.update_budget_progress(30.0);
+    assert!(app.budget_warning().unwrap().ends_with("is used up"));
+}
+
+#[test]
+fn test_config_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings").join("config.json");
+    assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+    let config = Config {
+        budgets: vec![Budget { tag: "migration".to_string(), hours: 10.0, period: BudgetPeriod::Month }],
+        ..Default::default()
+    };
+    config.save(&path).unwrap();
+    assert_eq!(Config::load(&path).unwrap(), config);
+    assert!(std::fs::read_to_string(&path).unwrap().contains("\"period\": \"month\""));
+}
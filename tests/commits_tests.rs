@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::commits::{self, CommitLoader, read_all_commits};
+use mypomodoro::history::PhaseRecord;
+use mypomodoro::report::{ReportPeriod, markdown_report_with_commits};
+
+fn git(repository: &Path, args: &[&str], date: &str) {
+    git_as(repository, "tester@example.com", args, date);
+}
+
+fn git_as(repository: &Path, email: &str, args: &[&str], date: &str) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Tester")
+        .env("GIT_AUTHOR_EMAIL", email)
+        .env("GIT_COMMITTER_NAME", "Tester")
+        .env("GIT_COMMITTER_EMAIL", "tester@example.com")
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn commit(repository: &Path, summary: &str, time: DateTime<Utc>) {
+    let date = time.to_rfc3339();
+    git(repository, &["commit", "--allow-empty", "--quiet", "-m", summary], &date);
+}
+
+fn pomodoro(start: DateTime<Utc>) -> PhaseRecord {
+    PhaseRecord {
+        start,
+        end: start + Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        task: "Parser".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_commits_are_matched_to_pomodoros() {
+    let dir = tempfile::tempdir().unwrap();
+    let repository = dir.path().join("parser");
+    std::fs::create_dir(&repository).unwrap();
+    git(&repository, &["init", "--quiet"], "");
+
+    let start = Utc::now() - Duration::hours(2);
+    commit(&repository, "Before the pomodoro", start - Duration::minutes(5));
+    commit(&repository, "Handle empty input", start + Duration::minutes(10));
+    commit(&repository, "Add parser tests", start + Duration::minutes(20));
+    commit(&repository, "After the pomodoro", start + Duration::minutes(40));
+
+    let (found, errors) = read_all_commits(&[repository, dir.path().join("missing")], None, Some("tester@example.com"));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(found.len(), 4);
+
+    let record = pomodoro(start);
+    let during: Vec<&str> = commits::commits_during(&found, &record)
+        .iter()
+        .map(|commit| commit.summary.as_str())
+        .collect();
+    assert_eq!(during, ["Handle empty input", "Add parser tests"]);
+    assert_eq!(found[1].repository, "parser");
+    assert_eq!(found[1].short_hash().len(), 7);
+
+    let today = record.local_date();
+    let report = markdown_report_with_commits(&[record], &found, &ReportPeriod::Day(today));
+    assert!(report.contains("## Commits"));
+    assert!(report.contains(&format!("  - `{}` Handle empty input (parser)", found[1].short_hash())));
+    assert!(!report.contains("After the pomodoro"));
+}
+
+#[test]
+fn test_read_commits_since_date() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "--quiet"], "");
+    commit(dir.path(), "Old work", "2020-01-01T12:00:00Z".parse().unwrap());
+    commit(dir.path(), "New work", Utc::now());
+
+    let since = NaiveDate::from_ymd_opt(2025, 1, 1);
+    let (found, errors) = read_all_commits(&[dir.path().to_path_buf()], since, Some("tester@example.com"));
+    assert!(errors.is_empty());
+    let summaries: Vec<&str> = found.iter().map(|commit| commit.summary.as_str()).collect();
+    assert_eq!(summaries, ["New work"]);
+}
+
+#[test]
+fn test_read_commits_of_the_author_only() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "--quiet"], "");
+    git(dir.path(), &["config", "user.email", "tester@example.com"], "");
+    let now = Utc::now().to_rfc3339();
+    git(dir.path(), &["commit", "--allow-empty", "--quiet", "-m", "Mine"], &now);
+    git_as(dir.path(), "other@example.com", &["commit", "--allow-empty", "--quiet", "-m", "Teammate's"], &now);
+    git_as(dir.path(), "x-tester@example.com", &["commit", "--allow-empty", "--quiet", "-m", "Similar"], &now);
+
+    let repositories = [dir.path().to_path_buf()];
+    let summaries = |author: Option<&str>| -> Vec<String> {
+        let (found, errors) = read_all_commits(&repositories, None, author);
+        assert!(errors.is_empty(), "{errors:?}");
+        found.into_iter().map(|commit| commit.summary).collect()
+    };
+    assert_eq!(summaries(None), ["Mine"]);
+    assert_eq!(summaries(Some("other@example.com")), ["Teammate's"]);
+}
+
+#[test]
+fn test_commit_loader_reads_in_the_background() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "--quiet"], "");
+    commit(dir.path(), "Background work", Utc::now());
+
+    let mut loader = CommitLoader::default();
+    let today = chrono::Local::now().date_naive();
+    assert!(!loader.is_enabled());
+    loader.configure(&[dir.path().to_path_buf()], Some("tester@example.com"));
+    assert!(loader.is_enabled());
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let loaded = loop {
+        if let Some(loaded) = loader.commits_since(today) {
+            break loaded.clone();
+        }
+        assert!(std::time::Instant::now() < deadline, "commits were not loaded");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    assert_eq!(loaded.commits.len(), 1);
+    assert!(loaded.errors.is_empty());
+}
@@ -0,0 +1,83 @@
+use chrono::{Local, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::export::ExportFormat;
+use mypomodoro::export::csv::{escape_field, write_csv};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+fn record_at(day: u32, hour: u32, task: &str) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        pause_min: 0.0,
+        outcome: PhaseOutcome::Completed,
+        tag: "work".to_string(),
+        task: task.to_string(),
+        interruptions: 2,
+        ..Default::default()
+    }
+}
+
+fn export(records: &[PhaseRecord], range: &DateRange) -> String {
+    let mut buffer = Vec::new();
+    write_csv(&mut buffer, records, range).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn test_escape_field() {
+    assert_eq!(escape_field("plain"), "plain");
+    assert_eq!(escape_field("a,b"), "\"a,b\"");
+    assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(escape_field("two\nlines"), "\"two\nlines\"");
+}
+
+#[test]
+fn test_write_csv_rows() {
+    let csv = export(&[record_at(18, 9, "Review, merge")], &DateRange::default());
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+
+    assert_eq!(
+        lines[0],
+        "start,end,phase,planned_minutes,actual_minutes,pause_minutes,outcome,tag,task,interruptions"
+    );
+    assert_eq!(
+        lines[1],
+        "2026-10-18 09:00:00,2026-10-18 09:25:00,Pomodoro,25.0,25.0,0.0,completed,work,\"Review, merge\",2"
+    );
+    assert_eq!(lines[2], "");
+}
+
+#[test]
+fn test_write_csv_filters_by_date_range() {
+    let records = [record_at(16, 9, "old"), record_at(17, 9, "kept"), record_at(18, 9, "new")];
+    let range = DateRange::parse("2026-10-17", "2026-10-17").unwrap();
+
+    let csv = export(&records, &range);
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.contains("kept"));
+}
+
+#[test]
+fn test_parse_export_csv_args() {
+    let args = ["--export-csv", "out.csv", "--from", "2026-10-01"].map(String::from);
+    match cli::parse_args(args).unwrap() {
+        Command::Export { format, output, range, .. } => {
+            assert_eq!(format, ExportFormat::Csv);
+            assert_eq!(output.to_str(), Some("out.csv"));
+            assert!(range.from.is_some());
+            assert!(range.to.is_none());
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+
+    assert_eq!(cli::parse_args(Vec::<String>::new()).unwrap(), Command::Gui);
+    assert!(cli::parse_args(["--from".to_string()]).is_err());
+    assert!(cli::parse_args(["--bogus".to_string()]).is_err());
+    let reversed = ["--export-csv", "out.csv", "--from", "2026-10-20", "--to", "2026-10-01"].map(String::from);
+    assert!(cli::parse_args(reversed).is_err());
+}
@@ -0,0 +1,94 @@
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::history::{History, HistoryError, PhaseOutcome, PhaseRecord};
+use mypomodoro::history_editor::RecordForm;
+
+fn form(start: &str, end: &str) -> RecordForm {
+    RecordForm {
+        start: start.to_string(),
+        end: end.to_string(),
+        tag: " kitchen ".to_string(),
+        ..RecordForm::new(NaiveDate::from_ymd_opt(2026, 10, 18).unwrap())
+    }
+}
+
+fn local(hour: u32, minute: u32) -> chrono::DateTime<Utc> {
+    Local.with_ymd_and_hms(2026, 10, 18, hour, minute, 0).unwrap().with_timezone(&Utc)
+}
+
+#[test]
+fn test_form_to_record() {
+    let record = form("09:00", "09:25").to_record(None).unwrap();
+
+    assert_eq!(record.start, local(9, 0));
+    assert_eq!(record.end, local(9, 25));
+    assert_eq!(record.phase, TimerPhase::Pomodoro);
+    assert_eq!(record.outcome, PhaseOutcome::Completed);
+    assert_eq!(record.planned_min, 25.0);
+    assert_eq!(record.actual_min, 25.0);
+    assert_eq!(record.tag, "kitchen");
+
+    assert!(form("9am", "09:25").to_record(None).is_err());
+}
+
+#[test]
+fn test_form_keeps_hidden_fields_when_editing() {
+    let base = PhaseRecord {
+        planned_min: 25.0,
+        pause_min: 5.0,
+        note: "kept".to_string(),
+        ..form("09:00", "09:30").to_record(None).unwrap()
+    };
+
+    let edited = form("09:00", "09:40").to_record(Some(&base)).unwrap();
+    assert_eq!(edited.planned_min, 25.0);
+    assert_eq!(edited.actual_min, 35.0);
+    assert_eq!(edited.note, "kept");
+}
+
+#[test]
+fn test_form_round_trips_records_across_midnight() {
+    let start = local(23, 50) + chrono::Duration::seconds(17);
+    let base = PhaseRecord {
+        start,
+        end: start + chrono::Duration::seconds(25 * 60 + 8),
+        tag: "old".to_string(),
+        ..Default::default()
+    };
+
+    let mut edited = RecordForm::from_record(&base);
+    assert_eq!(edited.end_date, "2026-10-19");
+    edited.tag = "fixed".to_string();
+    let record = edited.to_record(Some(&base)).unwrap();
+    assert_eq!(record.start, base.start);
+    assert_eq!(record.end, base.end);
+    assert_eq!(record.tag, "fixed");
+
+    edited.start = "23:45".to_string();
+    assert_eq!(edited.to_record(Some(&base)).unwrap().start, local(23, 45));
+}
+
+#[test]
+fn test_add_edit_and_remove_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut history = History::open(dir.path(), "desk").unwrap();
+
+    let first = history.add_record(form("09:00", "09:25").to_record(None).unwrap()).unwrap();
+    let second = history.add_record(form("10:00", "10:25").to_record(None).unwrap()).unwrap();
+
+    let overlapping = form("09:20", "09:45").to_record(None).unwrap();
+    assert!(matches!(history.add_record(overlapping), Err(HistoryError::Invalid(_))));
+    let backwards = form("11:00", "10:30").to_record(None).unwrap();
+    assert!(matches!(history.add_record(backwards), Err(HistoryError::Invalid(_))));
+
+    // Moving a record over its own old time is fine, over another record is not.
+    let base = history.get(&first).unwrap().clone();
+    history.edit_record(form("09:05", "09:30").to_record(Some(&base)).unwrap()).unwrap();
+    assert!(history.edit_record(form("09:50", "10:10").to_record(Some(&base)).unwrap()).is_err());
+
+    history.remove_record(&second).unwrap();
+
+    let reloaded = History::open(dir.path(), "desk").unwrap();
+    assert_eq!(reloaded.records().len(), 1);
+    assert_eq!(reloaded.records()[0].start, local(9, 5));
+}
@@ -0,0 +1,64 @@
+use chrono::{TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::history::{History, PhaseRecord};
+
+fn pomodoro(hour: u32, task: &str) -> PhaseRecord {
+    let start = Utc.with_ymd_and_hms(2026, 10, 18, hour, 0, 0).unwrap();
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        task: task.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_each_machine_writes_its_own_journal() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut desktop = History::open(dir.path(), "desktop").unwrap();
+    let mut laptop = History::open(dir.path(), "laptop").unwrap();
+
+    desktop.append(pomodoro(9, "Report")).unwrap();
+    laptop.append(pomodoro(10, "Email")).unwrap();
+
+    assert!(dir.path().join("journal-desktop.jsonl").exists());
+    assert!(dir.path().join("journal-laptop.jsonl").exists());
+
+    assert!(desktop.refresh().unwrap());
+    assert!(!desktop.refresh().unwrap());
+    let tasks: Vec<&str> = desktop.records().iter().map(|record| record.task.as_str()).collect();
+    assert_eq!(tasks, ["Report", "Email"]);
+}
+
+#[test]
+fn test_edits_and_deletes_reconcile_across_machines() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut desktop = History::open(dir.path(), "desktop").unwrap();
+    let report = desktop.append(pomodoro(9, "Report")).unwrap();
+    let email = desktop.append(pomodoro(10, "Email")).unwrap();
+
+    let mut laptop = History::open(dir.path(), "laptop").unwrap();
+    let mut edited = laptop.get(&report).unwrap().clone();
+    edited.tag = "writing".to_string();
+    laptop.update(edited).unwrap();
+    desktop.remove_record(&email).unwrap();
+
+    desktop.refresh().unwrap();
+    laptop.refresh().unwrap();
+    for history in [&desktop, &laptop] {
+        assert_eq!(history.records().len(), 1);
+        assert_eq!(history.get(&report).unwrap().tag, "writing");
+        assert!(history.get(&email).is_none());
+    }
+
+    // A later edit on the desktop wins over the laptop's earlier one.
+    let mut edited = desktop.get(&report).unwrap().clone();
+    edited.tag = "docs".to_string();
+    desktop.update(edited).unwrap();
+    laptop.refresh().unwrap();
+    assert_eq!(laptop.get(&report).unwrap().tag, "docs");
+}
+
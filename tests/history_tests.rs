@@ -0,0 +1,156 @@
+use mypomodoro::history::{DateRange, History, PhaseOutcome};
+use mypomodoro::{MyApp, TimerPhase};
+
+#[test]
+fn test_skip_phase_records_skipped_phase() {
+    let mut app = MyApp::default();
+    app.set_task("Write report");
+    app.set_tag("work");
+
+    app.begin_timer();
+    app.record_interruption();
+    app.skip_phase();
+
+    let records = app.history().records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].phase, TimerPhase::Pomodoro);
+    assert_eq!(records[0].outcome, PhaseOutcome::Skipped);
+    assert_eq!(records[0].task, "Write report");
+    assert_eq!(records[0].tag, "work");
+    assert_eq!(records[0].interruptions, 1);
+    assert_eq!(records[0].planned_min, 25.0);
+    assert_eq!(app.interruptions(), 0);
+}
+
+#[test]
+fn test_reset_timer_records_voided_phase() {
+    let mut app = MyApp::default();
+
+    app.reset_timer();
+    assert!(app.history().records().is_empty());
+
+    app.begin_timer();
+    app.pause_timer();
+    app.reset_timer();
+
+    let records = app.history().records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].outcome, PhaseOutcome::Voided);
+    assert!(records[0].pause_min >= 0.0);
+}
+
+#[test]
+fn test_interruptions_only_count_during_pomodoro() {
+    let mut app = MyApp::default();
+
+    app.record_interruption();
+    assert_eq!(app.interruptions(), 0);
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    app.record_interruption();
+    assert_eq!(app.interruptions(), 0);
+}
+
+#[test]
+fn test_history_persists_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap());
+    app.begin_timer();
+    app.skip_phase();
+    app.skip_phase();
+
+    let reloaded = History::open(dir.path(), "desk").unwrap();
+    assert_eq!(reloaded.records(), app.history().records());
+    assert_eq!(reloaded.records()[1].phase, TimerPhase::ShortBreak);
+}
+
+#[test]
+fn test_history_ignores_truncated_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("journal-desk.jsonl");
+
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap());
+    app.begin_timer();
+    app.skip_phase();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"start\":"))
+        .unwrap();
+
+    assert_eq!(History::open(dir.path(), "desk").unwrap().records().len(), 1);
+}
+
+#[test]
+fn test_history_appends_after_truncated_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("journal-desk.jsonl");
+
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap());
+    app.begin_timer();
+    app.skip_phase();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"start\":"))
+        .unwrap();
+
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap());
+    app.begin_timer();
+    app.skip_phase();
+    assert_eq!(History::open(dir.path(), "desk").unwrap().records().len(), 2);
+}
+
+#[test]
+fn test_date_range_parse() {
+    let range = DateRange::parse("2026-10-01", "").unwrap();
+    assert!(range.from.is_some());
+    assert!(range.to.is_none());
+
+    assert!(DateRange::parse("2026-10-05", "2026-10-01").is_err());
+    assert!(DateRange::parse("10/01/2026", "").is_err());
+}
+
+#[test]
+fn test_intention_prompt_and_record() {
+    let mut app = MyApp::default();
+
+    app.begin_timer_with_phase(TimerPhase::ShortBreak);
+    assert!(!app.show_intention_prompt());
+
+    app.begin_timer();
+    assert!(app.show_intention_prompt());
+    app.set_intention("Outline the talk");
+    app.skip_phase();
+
+    assert_eq!(app.history().records()[0].intention, "Outline the talk");
+    assert_eq!(app.intention(), "");
+    assert!(!app.show_intention_prompt());
+
+    app.complete_phase();
+    assert_eq!(app.current_phase(), TimerPhase::Pomodoro);
+    assert!(app.show_intention_prompt());
+}
+
+#[test]
+fn test_reflection_is_stored_with_completed_pomodoro() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = MyApp::with_history(History::open(dir.path(), "desk").unwrap());
+
+    app.begin_timer();
+    app.complete_phase();
+    assert_eq!(app.current_phase(), TimerPhase::ShortBreak);
+    assert_eq!(app.pending_reflection(), Some(app.history().records()[0].id.as_str()));
+
+    app.submit_reflection(9, " Got into flow \n");
+    assert_eq!(app.pending_reflection(), None);
+
+    let reloaded = History::open(dir.path(), "desk").unwrap();
+    assert_eq!(reloaded.records()[0].focus_rating, Some(5));
+    assert_eq!(reloaded.records()[0].note, "Got into flow");
+
+    // Finishing a break does not ask for a reflection.
+    app.complete_phase();
+    assert_eq!(app.pending_reflection(), None);
+}
@@ -0,0 +1,95 @@
+use chrono::{TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::export::ical::{escape_text, fold_line, write_ics};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+fn record(phase: TimerPhase, hour: u32, minute: u32, length_min: i64, task: &str) -> PhaseRecord {
+    let start = Utc.with_ymd_and_hms(2026, 10, 18, hour, minute, 0).unwrap();
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(length_min),
+        phase,
+        planned_min: length_min as f32,
+        actual_min: length_min as f32,
+        pause_min: 0.0,
+        outcome: PhaseOutcome::Completed,
+        tag: "work".to_string(),
+        task: task.to_string(),
+        interruptions: 0,
+        ..Default::default()
+    }
+}
+
+fn export(records: &[PhaseRecord], merge: bool) -> String {
+    let mut buffer = Vec::new();
+    write_ics(&mut buffer, records, &DateRange::default(), merge).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn morning() -> Vec<PhaseRecord> {
+    vec![
+        record(TimerPhase::Pomodoro, 9, 0, 25, "Report"),
+        record(TimerPhase::ShortBreak, 9, 25, 5, "Report"),
+        record(TimerPhase::Pomodoro, 9, 30, 25, "Report"),
+        record(TimerPhase::ShortBreak, 9, 55, 5, "Report"),
+        record(TimerPhase::Pomodoro, 10, 0, 25, "Email"),
+    ]
+}
+
+#[test]
+fn test_write_ics_event() {
+    let ics = export(&morning()[..1], false);
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains(
+        "BEGIN:VEVENT\r\n\
+         UID:20261018T090000Z-pomodoro@mypomodoro\r\n\
+         DTSTAMP:20261018T092500Z\r\n\
+         DTSTART:20261018T090000Z\r\n\
+         DTEND:20261018T092500Z\r\n\
+         SUMMARY:Pomodoro [work]\r\n\
+         DESCRIPTION:Report\r\n"
+    ));
+}
+
+#[test]
+fn test_write_ics_is_stable_and_skips_unfinished_phases() {
+    let mut records = morning();
+    records[4].outcome = PhaseOutcome::Voided;
+
+    let ics = export(&records, false);
+    assert_eq!(ics, export(&records, false));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 4);
+}
+
+#[test]
+fn test_write_ics_merges_consecutive_pomodoros() {
+    let ics = export(&morning(), true);
+
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+    assert!(ics.contains("DTSTART:20261018T090000Z\r\nDTEND:20261018T095500Z\r\nSUMMARY:2 Pomodoros [work]"));
+    assert!(ics.contains("UID:20261018T095500Z-short-break@mypomodoro"));
+    assert!(ics.contains("UID:20261018T100000Z-pomodoro@mypomodoro"));
+}
+
+#[test]
+fn test_escape_and_fold() {
+    assert_eq!(escape_text("a,b;c\\d\ne"), r"a\,b\;c\\d\ne");
+
+    let line = format!("DESCRIPTION:{}", "あ".repeat(40));
+    let folded = fold_line(&line);
+    assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+    assert_eq!(folded.replace("\r\n ", ""), line);
+}
+
+#[test]
+fn test_parse_export_ics_args() {
+    let args = ["--export-ics", "-", "--merge"].map(String::from);
+    match cli::parse_args(args).unwrap() {
+        Command::Export { options, .. } => assert!(options.merge_consecutive),
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(cli::parse_args(["--merge".to_string()]).is_err());
+}
@@ -0,0 +1,128 @@
+use chrono::{Local, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::history::{History, PhaseRecord};
+use mypomodoro::import::{self, ColumnMapping, ImportForm, ImportPreset};
+
+const TOGGL_CSV: &str = "\
+\u{feff}User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\r
+Ann,ann@example.com,,Web,,\"Fix login, again\",No,2026-10-15,09:00:00,2026-10-15,09:25:00,00:25:00,backend\r
+Ann,ann@example.com,,Web,,Write docs,No,2026-10-15,10:00:00,2026-10-15,10:25:00,00:25:00,\r
+Ann,ann@example.com,,Web,,Broken row,No,2026-10-15,not a time,2026-10-15,11:25:00,00:25:00,\r
+Ann,ann@example.com,,Web,,Backwards,No,2026-10-15,12:30:00,2026-10-15,12:00:00,00:30:00,\r
+Ann,ann@example.com,,Web,,Same again,No,2026-10-15,09:00:00,2026-10-15,09:25:00,00:25:00,backend\r
+";
+
+fn local(hour: u32, minute: u32) -> chrono::DateTime<Utc> {
+    Local.with_ymd_and_hms(2026, 10, 15, hour, minute, 0).unwrap().with_timezone(&Utc)
+}
+
+#[test]
+fn test_parse_csv_quotes() {
+    let rows = import::parse_csv("a,\"b, \"\"c\"\"\",\"line\nbreak\"\r\n1,2,3");
+    assert_eq!(rows, [vec!["a", "b, \"c\"", "line\nbreak"], vec!["1", "2", "3"]]);
+}
+
+#[test]
+fn test_preview_toggl_export() {
+    // The first record is the second row rounded differently, the other merely overlaps the first row.
+    let existing = vec![
+        PhaseRecord {
+            start: local(10, 1),
+            end: local(10, 24),
+            phase: TimerPhase::Pomodoro,
+            ..Default::default()
+        },
+        PhaseRecord {
+            start: local(9, 10),
+            end: local(9, 40),
+            phase: TimerPhase::Pomodoro,
+            ..Default::default()
+        },
+    ];
+    let preview = import::preview(TOGGL_CSV, &ImportPreset::Toggl.mapping(), &existing).unwrap();
+
+    assert_eq!(preview.records.len(), 1);
+    assert_eq!(preview.records[0].start, local(9, 0));
+    assert_eq!(preview.records[0].actual_min, 25.0);
+    assert_eq!(preview.records[0].task, "Fix login, again");
+    assert_eq!(preview.records[0].tag, "backend");
+
+    let duplicate_lines: Vec<usize> = preview.duplicates.iter().map(|(line, _)| *line).collect();
+    assert_eq!(duplicate_lines, [3, 6]);
+    let failed_lines: Vec<usize> = preview.failures.iter().map(|failure| failure.line).collect();
+    assert_eq!(failed_lines, [4, 5]);
+    assert!(preview.report().starts_with("1 to import, 2 duplicates, 2 failed\nline 4: Invalid time"));
+}
+
+#[test]
+fn test_preview_with_duration_column() {
+    let csv = "When,Minutes,What\n2026-10-15 14:00,25,Review\n2026-10-15 15:00,0:50:00,Plan\n2026-10-15 16:00,-5,Oops\n";
+    let form = ImportForm {
+        start: "When".to_string(),
+        end: String::new(),
+        duration: "Minutes".to_string(),
+        label: "What".to_string(),
+        tag: String::new(),
+        time_format: "%Y-%m-%d %H:%M".to_string(),
+    };
+    let preview = import::preview(csv, &form.to_mapping(), &[]).unwrap();
+
+    assert_eq!(preview.records.len(), 2);
+    assert_eq!(preview.records[1].end, local(15, 50));
+    assert_eq!(preview.failures.len(), 1);
+
+    let missing = ColumnMapping { start: vec!["Begin".to_string()], ..form.to_mapping() };
+    assert_eq!(import::preview(csv, &missing, &[]), Err("Column 'Begin' not found".to_string()));
+}
+
+#[test]
+fn test_preview_reports_physical_lines_and_rejects_bad_durations() {
+    let csv = "When,Minutes,What\n\
+               2026-10-15 14:00,25,\"Two\nlines\"\n\
+               2026-10-15 15:00,inf,Infinite\n\
+               2026-10-15 15:00,1e300,Huge\n\
+               2026-10-15 15:00,99999999999999999:00,Overflow\n\
+               2026-10-15 15:00,1:-30:00,Negative\n\
+               2026-10-15 15:00,NaN,Not a number\n";
+    let form = ImportForm {
+        start: "When".to_string(),
+        end: String::new(),
+        duration: "Minutes".to_string(),
+        label: "What".to_string(),
+        tag: String::new(),
+        time_format: "%Y-%m-%d %H:%M".to_string(),
+    };
+    let preview = import::preview(csv, &form.to_mapping(), &[]).unwrap();
+
+    assert_eq!(preview.records.len(), 1);
+    assert_eq!(preview.records[0].task, "Two\nlines");
+    let failed_lines: Vec<usize> = preview.failures.iter().map(|failure| failure.line).collect();
+    assert_eq!(failed_lines, [4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_import_command_dry_run_and_import() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("toggl.csv");
+    std::fs::write(&input, TOGGL_CSV).unwrap();
+    let mut history = History::open(dir.path().join("history"), "desk").unwrap();
+    let args = |extra: &[&str]| {
+        let mut args = vec!["import".to_string(), input.display().to_string(), "--preset".to_string(), "toggl".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        cli::parse_args(args).unwrap()
+    };
+
+    let dry_run = args(&["--dry-run"]);
+    assert!(matches!(dry_run, Command::Import { dry_run: true, .. }));
+    cli::run(&dry_run, &mut history, &Default::default()).unwrap();
+    assert!(history.records().is_empty());
+
+    cli::run(&args(&[]), &mut history, &Default::default()).unwrap();
+    assert_eq!(history.records().len(), 2);
+    // Importing the same file again only finds duplicates.
+    cli::run(&args(&[]), &mut history, &Default::default()).unwrap();
+    assert_eq!(history.records().len(), 2);
+
+    assert!(cli::parse_args(["import".to_string()]).is_err());
+}
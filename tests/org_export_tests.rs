@@ -0,0 +1,118 @@
+use chrono::{Local, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::export::org::{OrgGrouping, append_org, clock_line, write_org};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+fn pomodoro(hour: u32, task: &str, tag: &str) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, 10, 18, hour, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        pause_min: 0.0,
+        outcome: PhaseOutcome::Completed,
+        tag: tag.to_string(),
+        task: task.to_string(),
+        interruptions: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_clock_line_format() {
+    assert_eq!(
+        clock_line(&pomodoro(9, "Report", "work")),
+        "CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 09:25] =>  0:25"
+    );
+}
+
+#[test]
+fn test_write_org_groups_by_tag() {
+    let mut voided = pomodoro(11, "Email", "admin");
+    voided.outcome = PhaseOutcome::Voided;
+    let records = [pomodoro(9, "Report", "work"), pomodoro(10, "Email", "work"), voided];
+
+    let mut buffer = Vec::new();
+    write_org(&mut buffer, &records, &DateRange::default(), OrgGrouping::Tag).unwrap();
+    let org = String::from_utf8(buffer).unwrap();
+
+    assert_eq!(
+        org,
+        "* work\n\
+         :LOGBOOK:\n\
+         CLOCK: [2026-10-18 Sun 10:00]--[2026-10-18 Sun 10:25] =>  0:25\n\
+         CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 09:25] =>  0:25\n\
+         :END:\n"
+    );
+}
+
+#[test]
+fn test_append_org_is_idempotent() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("time.org");
+    std::fs::write(&path, "#+TITLE: Time\n* Report\n  Some notes\n").unwrap();
+
+    let mut records = vec![pomodoro(9, "Report", "work"), pomodoro(10, "Email", "")];
+    assert_eq!(append_org(&path, &records, &DateRange::default(), OrgGrouping::Task).unwrap(), 2);
+    assert_eq!(append_org(&path, &records, &DateRange::default(), OrgGrouping::Task).unwrap(), 0);
+
+    records.push(pomodoro(11, "Report", "work"));
+    assert_eq!(append_org(&path, &records, &DateRange::default(), OrgGrouping::Task).unwrap(), 1);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        content,
+        "#+TITLE: Time\n\
+         * Report\n\
+         :LOGBOOK:\n\
+         CLOCK: [2026-10-18 Sun 11:00]--[2026-10-18 Sun 11:25] =>  0:25\n\
+         CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 09:25] =>  0:25\n\
+         :END:\n\
+         \x20 Some notes\n\
+         * Email\n\
+         :LOGBOOK:\n\
+         CLOCK: [2026-10-18 Sun 10:00]--[2026-10-18 Sun 10:25] =>  0:25\n\
+         :END:\n"
+    );
+}
+
+#[test]
+fn test_append_org_keeps_planning_and_drawers_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("time.org");
+    std::fs::write(
+        &path,
+        "* Report\n\
+         SCHEDULED: <2026-10-18 Sun>\n\
+         :PROPERTIES:\n\
+         :EFFORT: 2:00\n\
+         :END:\n\
+         Body\n",
+    )
+    .unwrap();
+
+    let mut earlier = pomodoro(9, "Report", "work");
+    earlier.utc_offset_s = Some(9 * 3600);
+    assert_eq!(append_org(&path, &[earlier.clone()], &DateRange::default(), OrgGrouping::Task).unwrap(), 1);
+    let later = pomodoro(10, "Report", "work");
+    assert_eq!(append_org(&path, &[earlier.clone(), later], &DateRange::default(), OrgGrouping::Task).unwrap(), 1);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(&lines[..5], ["* Report", "SCHEDULED: <2026-10-18 Sun>", ":PROPERTIES:", ":EFFORT: 2:00", ":END:"]);
+    assert_eq!(lines[5], ":LOGBOOK:");
+    assert!(lines[6].starts_with("CLOCK: [2026-10-18 Sun 10:00]"));
+    assert_eq!(lines[7], clock_line(&earlier));
+    assert_eq!(&lines[8..], [":END:", "Body"]);
+}
+
+#[test]
+fn test_clock_line_uses_recorded_time_zone() {
+    let mut record = pomodoro(9, "Report", "work");
+    record.start = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+    record.end = record.start + chrono::Duration::minutes(25);
+    record.utc_offset_s = Some(2 * 3600);
+    assert_eq!(clock_line(&record), "CLOCK: [2026-10-18 Sun 09:00]--[2026-10-18 Sun 09:25] =>  0:25");
+}
@@ -0,0 +1,60 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+use mypomodoro::MyApp;
+use mypomodoro::ambient::{AmbientConfig, AmbientLayer, AmbientSource, NoiseColor};
+use mypomodoro::config::Config;
+use mypomodoro::quiet::{QuietConfig, QuietSchedule};
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+/// Returns a local time on the week of Monday 2026-10-19.
+fn at(day: Weekday, hour: u32, minute: u32) -> NaiveDateTime {
+    let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+    (monday + chrono::Duration::days(day.num_days_from_monday() as i64)).and_time(time(hour, minute))
+}
+
+#[test]
+fn test_schedule_within_a_day() {
+    let schedule = QuietSchedule { days: vec![Weekday::Sat, Weekday::Sun], start: time(6, 0), end: time(9, 30) };
+    assert!(schedule.contains(at(Weekday::Sat, 6, 0)));
+    assert!(schedule.contains(at(Weekday::Sun, 9, 29)));
+    assert!(!schedule.contains(at(Weekday::Sun, 9, 30)));
+    assert!(!schedule.contains(at(Weekday::Mon, 7, 0)));
+}
+
+#[test]
+fn test_schedule_past_midnight() {
+    let schedule = QuietSchedule { days: vec![Weekday::Fri], start: time(22, 0), end: time(7, 0) };
+    assert!(schedule.contains(at(Weekday::Fri, 23, 0)));
+    assert!(schedule.contains(at(Weekday::Sat, 6, 59)));
+    assert!(!schedule.contains(at(Weekday::Sat, 22, 30)));
+    assert!(!schedule.contains(at(Weekday::Fri, 6, 0)));
+
+    let config = QuietConfig { schedules: vec![schedule], ..Default::default() };
+    assert!(config.is_quiet_at(at(Weekday::Sat, 1, 0)));
+    assert!(!QuietConfig::default().is_quiet_at(at(Weekday::Sat, 1, 0)));
+}
+
+#[test]
+fn test_quiet_override_silences_ambient() {
+    let ambient = AmbientConfig {
+        enabled: true,
+        layers: vec![AmbientLayer { source: AmbientSource::Noise(NoiseColor::White), volume: 1.0 }],
+        duck_volume: 0.0,
+    };
+    let config = Config { ambient, ..Default::default() };
+    let mut app = MyApp::default().with_config(config, None);
+    app.begin_timer();
+    assert!(!app.is_quiet());
+    assert!(app.ambient_level() > 0.0);
+
+    app.toggle_quiet_override();
+    assert!(app.is_quiet());
+    assert!(app.quiet_until().is_some());
+    assert_eq!(app.ambient_level(), 0.0);
+
+    app.toggle_quiet_override();
+    assert!(!app.is_quiet());
+}
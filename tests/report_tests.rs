@@ -0,0 +1,78 @@
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::history::{PhaseOutcome, PhaseRecord};
+use mypomodoro::report::{ReportPeriod, format_minutes, markdown_report};
+
+fn pomodoro(day: u32, hour: u32, task: &str, outcome: PhaseOutcome) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        outcome,
+        tag: "work".to_string(),
+        task: task.to_string(),
+        interruptions: 1,
+        ..Default::default()
+    }
+}
+
+fn records() -> Vec<PhaseRecord> {
+    let mut noted = pomodoro(15, 10, "Report", PhaseOutcome::Completed);
+    noted.intention = "Draft the intro".to_string();
+    noted.focus_rating = Some(4);
+    noted.note = "Finished the intro".to_string();
+    vec![
+        pomodoro(15, 9, "Report", PhaseOutcome::Completed),
+        noted,
+        pomodoro(15, 11, "Email", PhaseOutcome::Voided),
+        pomodoro(16, 9, "Email", PhaseOutcome::Completed),
+        pomodoro(20, 9, "Email", PhaseOutcome::Completed),
+    ]
+}
+
+#[test]
+fn test_format_minutes() {
+    assert_eq!(format_minutes(25.0), "25 min");
+    assert_eq!(format_minutes(125.0), "2 h 05 min");
+}
+
+#[test]
+fn test_daily_report() {
+    let day = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+    let report = markdown_report(&records(), &ReportPeriod::Day(day));
+
+    assert!(report.starts_with("# Pomodoro report for Thursday, 2026-10-15\n"));
+    assert!(report.contains("- Completed pomodoros: 2\n- Focus time: 50 min\n- Interruptions: 3\n- Voided pomodoros: 1\n"));
+    assert!(report.contains("| Task | Pomodoros | Focus time |\n| --- | ---: | ---: |\n| Report | 2 | 50 min |\n\n"));
+    assert!(report.contains("- Average focus: 4.0 / 5\n"));
+    assert!(report.contains("## Journal\n\n- 10:00 (Report): Draft the intro → focus 4/5 — Finished the intro\n"));
+    assert!(!report.contains("## By day"));
+}
+
+#[test]
+fn test_weekly_report() {
+    let day = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    let report = markdown_report(&records(), &ReportPeriod::Week(day));
+
+    assert!(report.starts_with("# Pomodoro report for the week 2026-10-12 to 2026-10-18\n"));
+    assert!(report.contains("- Completed pomodoros: 3\n"));
+    assert!(report.contains("| Email | 1 | 25 min |\n| Report | 2 | 50 min |\n"));
+    assert!(report.contains("| 2026-10-15 Thu | 2 | 50 min |\n| 2026-10-16 Fri | 1 | 25 min |\n"));
+}
+
+#[test]
+fn test_parse_report_args() {
+    let args = ["report", "--week", "2026-10-14", "--output", "week.md"].map(String::from);
+    match cli::parse_args(args).unwrap() {
+        Command::Report { period, output } => {
+            assert_eq!(period, ReportPeriod::Week(NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()));
+            assert_eq!(output.unwrap().to_str(), Some("week.md"));
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(cli::parse_args(["report", "--export-csv", "x"].map(String::from)).is_err());
+}
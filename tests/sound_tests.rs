@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use mypomodoro::MyApp;
+use mypomodoro::audio::{AudioService, AudioStatus};
+use mypomodoro::config::Config;
+use mypomodoro::sound::{Sound, SoundEvent, SoundSlot, Volume};
+
+/// Writes a short 16-bit mono WAV file of silence.
+fn write_wav(path: &Path) {
+    let samples = 800u32;
+    let data_len = samples * 2;
+    let mut wav = Vec::new();
+    wav.extend(b"RIFF");
+    wav.extend((36 + data_len).to_le_bytes());
+    wav.extend(b"WAVEfmt ");
+    wav.extend(16u32.to_le_bytes());
+    wav.extend(1u16.to_le_bytes());
+    wav.extend(1u16.to_le_bytes());
+    wav.extend(8000u32.to_le_bytes());
+    wav.extend(16000u32.to_le_bytes());
+    wav.extend(2u16.to_le_bytes());
+    wav.extend(16u16.to_le_bytes());
+    wav.extend(b"data");
+    wav.extend(data_len.to_le_bytes());
+    wav.resize(wav.len() + data_len as usize, 0);
+    std::fs::write(path, wav).unwrap();
+}
+
+#[test]
+fn test_sound_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let wav = dir.path().join("chime.wav");
+    write_wav(&wav);
+    assert_eq!(Sound::from_file(&wav).unwrap().name(), "chime.wav");
+
+    let broken = dir.path().join("broken.mp3");
+    std::fs::write(&broken, b"not audio").unwrap();
+    assert!(Sound::from_file(&broken).unwrap_err().starts_with("Failed to decode"));
+    assert!(Sound::from_file(&dir.path().join("missing.ogg")).unwrap_err().starts_with("Failed to read"));
+}
+
+#[test]
+fn test_app_falls_back_to_bell() {
+    let dir = tempfile::tempdir().unwrap();
+    let broken = dir.path().join("broken.flac");
+    std::fs::write(&broken, b"not audio").unwrap();
+    let config_path = dir.path().join("config.json");
+
+    let config = Config { sound_file: Some(broken.clone()), ..Default::default() };
+    let mut app = MyApp::default().with_config(config, Some(config_path.clone()));
+    assert_eq!(app.sound().name(), "Bell");
+    assert!(app.sound_error().unwrap().contains("broken.flac"));
+
+    let wav = dir.path().join("chime.wav");
+    write_wav(&wav);
+    app.set_sound_file(Some(wav.clone()));
+    assert_eq!(app.sound().name(), "chime.wav");
+    assert_eq!(app.sound_error(), None);
+    assert_eq!(Config::load(&config_path).unwrap().sound_file, Some(wav));
+
+    // A file that cannot be decoded is rejected and the previous sound kept.
+    app.set_sound_file(Some(broken));
+    assert_eq!(app.sound().name(), "chime.wav");
+    assert!(app.sound_error().is_some());
+}
+
+#[test]
+fn test_event_sounds() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    let mut app = MyApp::default().with_config(Config::default(), Some(config_path.clone()));
+
+    // Only the ends of phases play a sound until the user enables the other events.
+    assert_eq!(app.event_sound(SoundEvent::ShortBreakEnd).unwrap().0.name(), "Bell");
+    assert!(app.event_sound(SoundEvent::Pause).is_none());
+    assert!(app.event_sound(SoundEvent::Skip).is_none());
+
+    let wav = dir.path().join("pause.wav");
+    write_wav(&wav);
+    app.set_event_sound_file(SoundEvent::Pause, Some(wav.clone()));
+    app.set_sound_slot(SoundEvent::Pause, true, 0.5);
+    let (sound, volume) = app.event_sound(SoundEvent::Pause).unwrap();
+    assert_eq!((sound.name(), volume), ("pause.wav", 0.5));
+
+    // The last pomodoro falls back to the pomodoro end sound until its own slot is enabled.
+    app.set_event_sound_file(SoundEvent::PomodoroEnd, Some(wav.clone()));
+    assert_eq!(app.event_sound(SoundEvent::LastPomodoroEnd).unwrap().0.name(), "pause.wav");
+    app.set_sound_slot(SoundEvent::LastPomodoroEnd, true, 0.2);
+    assert_eq!(app.event_sound(SoundEvent::LastPomodoroEnd).unwrap().0.name(), "Bell");
+
+    let saved = Config::load(&config_path).unwrap();
+    assert_eq!(saved.sound_slot(SoundEvent::Pause).file, Some(wav));
+    assert_eq!(saved.sound_slot(SoundEvent::Skip), SoundSlot::default_for(SoundEvent::Skip));
+}
+
+#[test]
+fn test_completion_event() {
+    let mut app = MyApp::default();
+    app.begin_timer();
+    let mut events = Vec::new();
+    for _ in 0..app.cycles() * 2 {
+        events.push(app.completion_event());
+        app.complete_phase();
+    }
+    let last = events.len() - 2;
+    assert_eq!(events[last], SoundEvent::LastPomodoroEnd);
+    assert_eq!(events[last + 1], SoundEvent::LongBreakEnd);
+    assert!(events[..last].iter().all(|event| matches!(event, SoundEvent::PomodoroEnd | SoundEvent::ShortBreakEnd)));
+}
+
+#[test]
+fn test_audio_device_is_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    let mut app = MyApp::default().with_config(Config::default(), Some(config_path.clone()));
+    assert_eq!(app.audio_device(), None);
+
+    app.set_audio_device(Some("USB Headset".to_string()));
+    assert_eq!(app.audio_device(), Some("USB Headset"));
+    let saved = Config::load(&config_path).unwrap();
+    assert_eq!(saved.audio_device.as_deref(), Some("USB Headset"));
+
+    // A device that is not connected is kept in the settings; the audio thread falls back to the default.
+    let app = MyApp::default().with_config(saved, Some(config_path));
+    assert_eq!(app.audio_device(), Some("USB Headset"));
+}
+
+#[test]
+fn test_audio_service_plays_without_blocking() {
+    let audio = AudioService::start();
+    audio.set_device(None);
+    // Sounds are decoded before they are sent, so a valid sound is accepted even without an output device.
+    assert!(audio.play(&Sound::bell(), 0.1).is_ok());
+}
+
+#[test]
+fn test_volume_and_mute() {
+    let volume = Volume { alert: 0.5, ambient: 0.2, muted: false };
+    assert_eq!(volume.alert_volume(0.4), 0.2);
+    assert_eq!(volume.ambient_volume(0.5), 0.1);
+    let muted = Volume { muted: true, ..volume };
+    assert_eq!(muted.alert_volume(0.4), 0.0);
+    assert_eq!(muted.ambient_volume(0.5), 0.0);
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.json");
+    let mut app = MyApp::default().with_config(Config::default(), Some(config_path.clone()));
+    assert_eq!(app.volume(), Volume::default());
+    app.set_volume(Volume { alert: 1.5, ambient: 0.3, muted: false });
+    assert_eq!(app.volume().alert, 1.0);
+
+    // Muting keeps the volumes and is saved together with them.
+    app.toggle_mute();
+    let saved = Config::load(&config_path).unwrap().volume;
+    assert_eq!(saved, Volume { alert: 1.0, ambient: 0.3, muted: true });
+    app.toggle_mute();
+    assert!(!Config::load(&config_path).unwrap().volume.muted);
+}
+
+#[test]
+fn test_audio_failure_is_shown() {
+    let mut app = MyApp::default();
+    assert_eq!(app.audio_error(), None);
+
+    app.handle_audio_status(AudioStatus::Notice("Audio device USB is not connected, using default".to_string()));
+    assert_eq!(app.audio_error(), None);
+    assert!(app.sound_error().unwrap().contains("USB"));
+
+    app.handle_audio_status(AudioStatus::AlertFailed("Failed to play sound: no audio output".to_string()));
+    assert_eq!(app.audio_error(), Some("Failed to play sound: no audio output"));
+
+    // The indicator clears once a device opens again.
+    app.handle_audio_status(AudioStatus::Connected("default".to_string()));
+    assert_eq!(app.audio_error(), None);
+}
+
+#[test]
+fn test_audio_service_reports_outcome() {
+    let audio = AudioService::start();
+    audio.play(&Sound::bell(), 0.0).unwrap();
+    // Either a device opens or the failure to play is reported; the outcome is never lost.
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let mut statuses = Vec::new();
+    let outcome = |statuses: &[AudioStatus]| {
+        statuses
+            .iter()
+            .any(|status| matches!(status, AudioStatus::Connected(_) | AudioStatus::AlertFailed(_)))
+    };
+    while !outcome(&statuses) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        statuses.extend(audio.take_statuses());
+    }
+    assert!(outcome(&statuses), "{statuses:?}");
+}
@@ -0,0 +1,91 @@
+use chrono::{Local, NaiveDate, TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+use mypomodoro::stats::{Stats, StatsGrouping};
+
+fn pomodoro(day: u32, hour: u32, tag: &str, outcome: PhaseOutcome) -> PhaseRecord {
+    let start = Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase: TimerPhase::Pomodoro,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        outcome,
+        tag: tag.to_string(),
+        interruptions: 1,
+        ..Default::default()
+    }
+}
+
+fn records() -> Vec<PhaseRecord> {
+    vec![
+        pomodoro(15, 9, "work", PhaseOutcome::Completed),
+        pomodoro(15, 10, "", PhaseOutcome::Completed),
+        pomodoro(15, 11, "work", PhaseOutcome::Voided),
+        pomodoro(16, 9, "work", PhaseOutcome::Completed),
+        pomodoro(20, 9, "work", PhaseOutcome::Completed),
+    ]
+}
+
+fn date(day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(2026, 10, day)
+}
+
+#[test]
+fn test_stats_grouped_by_tag() {
+    let stats = Stats::collect(&records(), &DateRange::new(date(15), date(16)), Some(StatsGrouping::Tag));
+
+    assert_eq!(stats.pomodoros, 3);
+    assert_eq!(stats.focus_min, 75.0);
+    assert_eq!(stats.voided, 1);
+    let rows: Vec<(&str, usize)> = stats.rows.iter().map(|row| (row.key.as_str(), row.pomodoros)).collect();
+    assert_eq!(rows, [("(none)", 1), ("work", 2)]);
+
+    assert_eq!(
+        stats.to_table(),
+        "        Pomodoros  Focus time  Interruptions\n\
+         (none)          1      25 min              1\n\
+         work            2      50 min              2\n\
+         Total           3  1 h 15 min              3\n\
+         Voided pomodoros: 1\n"
+    );
+}
+
+#[test]
+fn test_stats_json() {
+    let stats = Stats::collect(&records(), &DateRange::new(date(16), None), None);
+    let json: serde_json::Value = serde_json::to_value(&stats).unwrap();
+
+    assert_eq!(json["from"], "2026-10-16");
+    assert_eq!(json["to"], serde_json::Value::Null);
+    assert_eq!(json["pomodoros"], 2);
+    assert!(json.get("rows").is_none());
+}
+
+#[test]
+fn test_parse_stats_args() {
+    let args = |args: &[&str]| cli::parse_args(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(
+        args(&["stats", "--from", "2026-10-01", "--to", "2026-10-31", "--by", "day", "--json"]),
+        Ok(Command::Stats {
+            range: DateRange::new(date(1), date(31)),
+            grouping: Some(StatsGrouping::Day),
+            json: true,
+        })
+    );
+    let today = Local::now().date_naive();
+    assert!(matches!(
+        args(&["stats"]),
+        Ok(Command::Stats { range, grouping: None, json: false }) if range == DateRange::new(Some(today), Some(today))
+    ));
+    assert!(args(&["stats", "--week", "--from", "2026-10-01"]).is_err());
+    assert!(args(&["stats", "--from", "2026-10-05", "--to", "2026-10-01"]).is_err());
+    assert!(args(&["stats", "--by", "project"]).is_err());
+
+    // Read-only commands open the history without creating this machine's journal.
+    assert!(!args(&["stats", "--json"]).unwrap().writes_history());
+    assert!(args(&[]).unwrap().writes_history());
+}
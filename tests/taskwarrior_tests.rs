@@ -0,0 +1,108 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use mypomodoro::config::Config;
+use mypomodoro::history::{History, PhaseRecord};
+use mypomodoro::{MyApp, TimerPhase};
+use mypomodoro::taskwarrior::Taskwarrior;
+
+const EXPORT: &str = r#"[
+  {"id":1,"uuid":"aaaa-1111","description":"Write docs","status":"pending","urgency":2.5,"tags":["writing"]},
+  {"id":2,"uuid":"bbbb-2222","description":"Fix login","project":"web","status":"pending","urgency":8.1,"entry":"20261018T090000Z"}
+]"#;
+
+/// Writes a stub `task` script that logs its arguments and prints `EXPORT` for exports.
+fn stub_task(dir: &Path) -> (PathBuf, PathBuf) {
+    let log = dir.join("calls.log");
+    let export = dir.join("export.json");
+    std::fs::write(&export, EXPORT).unwrap();
+    let script = dir.join("task");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\ncase \"$*\" in *export*) cat '{}' ;; esac\n",
+            log.display(),
+            export.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    (script, log)
+}
+
+fn calls(log: &Path) -> Vec<String> {
+    std::fs::read_to_string(log)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.trim_start_matches("rc.confirmation=off rc.verbose=nothing ").to_string())
+        .collect()
+}
+
+#[test]
+fn test_pending_tasks_by_urgency() {
+    let dir = tempfile::tempdir().unwrap();
+    let (script, log) = stub_task(dir.path());
+
+    let tasks = Taskwarrior::new(&script).pending_tasks().unwrap();
+    let descriptions: Vec<&str> = tasks.iter().map(|task| task.description.as_str()).collect();
+    assert_eq!(descriptions, ["Fix login", "Write docs"]);
+    assert_eq!(tasks[0].project.as_deref(), Some("web"));
+    assert_eq!(calls(&log), ["status:pending export"]);
+
+    assert!(Taskwarrior::new(&dir.path().join("missing")).pending_tasks().is_err());
+}
+
+#[test]
+fn test_pomodoro_starts_stops_and_annotates_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let (script, log) = stub_task(dir.path());
+    let config = Config { taskwarrior_command: Some(script), ..Default::default() };
+    // A pomodoro on another task with the same description does not count towards this one.
+    let mut history = History::open(dir.path().join("history"), "desk").unwrap();
+    let start = chrono::Utc::now() - chrono::Duration::hours(2);
+    history
+        .append(PhaseRecord {
+            start,
+            end: start + chrono::Duration::minutes(25),
+            phase: TimerPhase::Pomodoro,
+            task: "Fix login".to_string(),
+            taskwarrior_uuid: "cccc-3333".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+    let mut app = MyApp::with_history(history).with_config(config, None);
+    app.wait_for_taskwarrior();
+
+    let task = app.taskwarrior_tasks()[0].clone();
+    app.select_taskwarrior_task(task);
+    assert_eq!(app.task(), "Fix login");
+    assert_eq!(app.tag(), "web");
+
+    app.begin_timer();
+    app.pause_timer();
+    app.resume_timer();
+    app.complete_phase();
+    // Finishing the break starts the next pomodoro and the task with it.
+    app.complete_phase();
+    app.wait_for_taskwarrior();
+
+    assert_eq!(
+        calls(&log),
+        [
+            "status:pending export",
+            "bbbb-2222 start",
+            "bbbb-2222 stop",
+            "bbbb-2222 start",
+            "bbbb-2222 stop",
+            "bbbb-2222 annotate Pomodoro 1 completed",
+            "bbbb-2222 start",
+        ]
+    );
+
+    app.reset_timer();
+    app.wait_for_taskwarrior();
+    assert_eq!(calls(&log).last().map(String::as_str), Some("bbbb-2222 stop"));
+    assert_eq!(app.history().records()[1].taskwarrior_uuid, "bbbb-2222");
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use mypomodoro::MyApp;
+use mypomodoro::config::Config;
+use mypomodoro::tick::{FASTEST_INTERVAL, TICK_SAMPLE_RATE, TickConfig, TickSource, synthesized_tick};
+
+/// Returns the sample positions at which ticks start, using a one-sample tick.
+fn tick_positions(config: TickConfig, remaining: Duration) -> (Vec<usize>, usize) {
+    let source = TickSource::new(Arc::from([1.0f32]), config, remaining);
+    let samples: Vec<f32> = source.collect();
+    let positions = samples.iter().enumerate().filter(|(_, sample)| **sample != 0.0).map(|(index, _)| index).collect();
+    (positions, samples.len())
+}
+
+#[test]
+fn test_ticks_land_on_whole_seconds() {
+    let rate = TICK_SAMPLE_RATE as usize;
+    let config = TickConfig { enabled: true, ..Default::default() };
+    let (positions, len) = tick_positions(config, Duration::from_millis(2500));
+    assert_eq!(positions, vec![rate / 2, rate / 2 + rate]);
+    assert_eq!(len, rate * 5 / 2);
+}
+
+#[test]
+fn test_source_resumed_later_stays_on_whole_seconds() {
+    let rate = TICK_SAMPLE_RATE as usize;
+    let config = TickConfig { enabled: true, ..Default::default() };
+    let source = TickSource::new(Arc::from([1.0f32]), config, Duration::from_millis(2500));
+    let samples: Vec<f32> = source.after(Duration::from_secs(1)).collect();
+    let positions: Vec<usize> = samples.iter().enumerate().filter(|(_, sample)| **sample != 0.0).map(|(index, _)| index).collect();
+    assert_eq!(positions, vec![rate / 2]);
+    assert_eq!(samples.len(), rate * 3 / 2);
+}
+
+#[test]
+fn test_ticks_only_in_last_minutes() {
+    let rate = TICK_SAMPLE_RATE as usize;
+    let config = TickConfig { enabled: true, last_minutes: 2, ..Default::default() };
+    let (positions, _) = tick_positions(config, Duration::from_secs(125));
+    assert_eq!(positions.len(), 120);
+    assert_eq!(positions[0], 5 * rate);
+}
+
+#[test]
+fn test_accelerating_interval() {
+    let config = TickConfig { accelerate: true, ..Default::default() };
+    assert_eq!(config.interval(120.0), Some(1.0));
+    assert_eq!(config.interval(30.0), Some(1.0 - (1.0 - FASTEST_INTERVAL) / 2.0));
+    assert!(config.interval(0.5).unwrap() < 0.3);
+    assert_eq!(config.interval(0.0), None);
+
+    let (positions, _) = tick_positions(TickConfig { accelerate: true, ..config }, Duration::from_secs(60));
+    let gaps: Vec<usize> = positions.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    assert!(gaps.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(positions.len() > 60);
+}
+
+#[test]
+fn test_ticking_follows_timer() {
+    assert!(!synthesized_tick().is_empty());
+    let config = Config { ticking: TickConfig { enabled: true, ..Default::default() }, ..Default::default() };
+    let mut app = MyApp::default().with_config(config, None);
+    assert!(!app.is_ticking());
+    app.begin_timer();
+    assert!(app.is_ticking());
+    app.pause_timer();
+    assert!(!app.is_ticking());
+    app.resume_timer();
+    app.complete_phase();
+    assert!(!app.is_ticking());
+}
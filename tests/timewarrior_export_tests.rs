@@ -0,0 +1,99 @@
+use chrono::{TimeZone, Utc};
+use mypomodoro::TimerPhase;
+use mypomodoro::cli::{self, Command};
+use mypomodoro::export::timewarrior::{export_timewarrior, interval_line};
+use mypomodoro::history::{DateRange, PhaseOutcome, PhaseRecord};
+
+fn record(phase: TimerPhase, month: u32, hour: u32, task: &str) -> PhaseRecord {
+    let start = Utc.with_ymd_and_hms(2026, month, 18, hour, 0, 0).unwrap();
+    PhaseRecord {
+        start,
+        end: start + chrono::Duration::minutes(25),
+        phase,
+        planned_min: 25.0,
+        actual_min: 25.0,
+        pause_min: 0.0,
+        outcome: PhaseOutcome::Completed,
+        tag: "work".to_string(),
+        task: task.to_string(),
+        interruptions: 0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_interval_line() {
+    assert_eq!(
+        interval_line(&record(TimerPhase::Pomodoro, 10, 9, "Write report")),
+        "inc 20261018T090000Z - 20261018T092500Z # work"
+    );
+
+    let mut quoted = record(TimerPhase::Pomodoro, 10, 9, "");
+    quoted.tag = "deep \"work\"".to_string();
+    assert_eq!(
+        interval_line(&quoted),
+        r#"inc 20261018T090000Z - 20261018T092500Z # "deep \"work\"""#
+    );
+
+    let mut untagged = record(TimerPhase::Pomodoro, 10, 9, "");
+    untagged.tag.clear();
+    assert_eq!(interval_line(&untagged), "inc 20261018T090000Z - 20261018T092500Z");
+}
+
+#[test]
+fn test_export_timewarrior_writes_monthly_files_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let records = [
+        record(TimerPhase::Pomodoro, 10, 10, "review"),
+        record(TimerPhase::Pomodoro, 10, 9, "review"),
+        record(TimerPhase::ShortBreak, 10, 11, ""),
+        record(TimerPhase::Pomodoro, 9, 9, "planning"),
+    ];
+
+    assert_eq!(export_timewarrior(dir.path(), &records, &DateRange::default()).unwrap(), 3);
+    assert_eq!(export_timewarrior(dir.path(), &records, &DateRange::default()).unwrap(), 0);
+
+    let october = std::fs::read_to_string(dir.path().join("data/2026-10.data")).unwrap();
+    assert_eq!(
+        october,
+        "inc 20261018T090000Z - 20261018T092500Z # work\n\
+         inc 20261018T100000Z - 20261018T102500Z # work\n"
+    );
+    assert!(dir.path().join("data/2026-09.data").exists());
+}
+
+#[test]
+fn test_export_timewarrior_merges_existing_lines_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    let existing = "inc 20261019T080000Z - 20261019T090000Z # meeting\n\
+                    inc 20261019T080000Z - 20261019T090000Z # meeting\n\
+                    inc 20261001T080000Z - 20261001T090000Z # meeting";
+    std::fs::write(data_dir.join("2026-10.data"), existing).unwrap();
+
+    let records = [record(TimerPhase::Pomodoro, 10, 9, "review")];
+    assert_eq!(export_timewarrior(dir.path(), &records, &DateRange::default()).unwrap(), 1);
+
+    let october = std::fs::read_to_string(data_dir.join("2026-10.data")).unwrap();
+    assert_eq!(
+        october,
+        "inc 20261001T080000Z - 20261001T090000Z # meeting\n\
+         inc 20261018T090000Z - 20261018T092500Z # work\n\
+         inc 20261019T080000Z - 20261019T090000Z # meeting\n\
+         inc 20261019T080000Z - 20261019T090000Z # meeting\n"
+    );
+    assert!(!data_dir.join("2026-10.data.tmp").exists());
+}
+
+#[test]
+fn test_parse_export_timew_args() {
+    let args = ["--export-timew", "--timew-dir", "/tmp/timew"].map(String::from);
+    match cli::parse_args(args).unwrap() {
+        Command::ExportTimewarrior { database_dir, .. } => {
+            assert_eq!(database_dir.unwrap().to_str(), Some("/tmp/timew"));
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(cli::parse_args(["--timew-dir", "x"].map(String::from)).is_err());
+}
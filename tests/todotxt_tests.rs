@@ -0,0 +1,132 @@
+use chrono::{Local, NaiveDate};
+use mypomodoro::MyApp;
+use mypomodoro::config::Config;
+use mypomodoro::todotxt::{TodoList, TodoTask};
+
+const TODO_TXT: &str = "\
+Call the bank @phone
+(B) 2026-10-01 Write the migration plan +migration @office due:2026-10-20
+x 2026-10-10 2026-10-02 Renew passport
+(A) Review PR 42 +backend +migration
+";
+
+#[test]
+fn test_parse_todo_line() {
+    let task = TodoTask::parse("(B) 2026-10-01 Write the migration plan +migration @office due:2026-10-20").unwrap();
+    assert_eq!(task.priority, Some('B'));
+    assert_eq!(task.creation_date, NaiveDate::from_ymd_opt(2026, 10, 1));
+    assert_eq!(task.projects, ["migration"]);
+    assert_eq!(task.contexts, ["office"]);
+    assert_eq!(task.title(), "Write the migration plan");
+    assert!(!task.done);
+
+    let done = TodoTask::parse("x 2026-10-10 2026-10-02 Renew passport").unwrap();
+    assert!(done.done);
+    assert_eq!(done.completion_date, NaiveDate::from_ymd_opt(2026, 10, 10));
+    assert_eq!(done.creation_date, NaiveDate::from_ymd_opt(2026, 10, 2));
+    assert_eq!(done.priority, None);
+
+    assert_eq!(TodoTask::parse("   "), None);
+    assert_eq!(TodoTask::parse("(a) lower case is not a priority").unwrap().priority, None);
+    assert_eq!(TodoTask::parse("Read https://example.com").unwrap().title(), "Read https://example.com");
+    assert_eq!(TodoTask::parse("Call Bob at 10:30 t:2026-10-19").unwrap().title(), "Call Bob at 10:30");
+    assert_eq!(TodoTask::parse("Fix bug: crash on start").unwrap().title(), "Fix bug: crash on start");
+}
+
+#[test]
+fn test_open_tasks_by_priority_and_completion() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("todo.txt");
+    std::fs::write(&path, TODO_TXT).unwrap();
+    let mut list = TodoList::open(&path).unwrap();
+
+    let titles: Vec<String> = list.open_tasks().iter().map(|task| task.title()).collect();
+    assert_eq!(titles, ["Review PR 42", "Write the migration plan", "Call the bank"]);
+
+    let task = list.open_tasks()[1].clone();
+    let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+    list.complete(&task, date).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        text.lines().nth(1),
+        Some("x 2026-10-19 2026-10-01 Write the migration plan +migration @office due:2026-10-20 pri:B")
+    );
+    assert!(text.ends_with('\n'));
+    assert_eq!(list.open_tasks().len(), 2);
+    assert!(list.complete(&task, date).is_err());
+}
+
+#[test]
+fn test_filter_open_tasks_by_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("todo.txt");
+    std::fs::write(&path, TODO_TXT).unwrap();
+    let list = TodoList::open(&path).unwrap();
+
+    assert_eq!(list.contexts(), ["office", "phone"]);
+    let titles: Vec<String> = list.open_tasks_in(Some("phone")).iter().map(|task| task.title()).collect();
+    assert_eq!(titles, ["Call the bank"]);
+    assert_eq!(list.open_tasks_in(None).len(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_complete_keeps_symlink_and_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("dotfiles-todo.txt");
+    std::fs::write(&target, TODO_TXT).unwrap();
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+    let link = dir.path().join("todo.txt");
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    let mut list = TodoList::open(&link).unwrap();
+    let task = list.open_tasks()[0].clone();
+    list.complete(&task, NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()).unwrap();
+
+    assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert!(std::fs::read_to_string(&target).unwrap().contains("x 2026-10-19 Review PR 42"));
+    assert_eq!(std::fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+}
+
+#[test]
+fn test_refresh_picks_up_external_edits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("todo.txt");
+    std::fs::write(&path, TODO_TXT).unwrap();
+    let mut list = TodoList::open(&path).unwrap();
+    assert!(!list.refresh().unwrap());
+
+    std::fs::write(&path, format!("{TODO_TXT}(C) Plan the offsite +team\n")).unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(1)).unwrap();
+
+    assert!(list.refresh().unwrap());
+    assert_eq!(list.open_tasks().len(), 4);
+}
+
+#[test]
+fn test_app_attributes_pomodoros_to_todo_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("todo.txt");
+    std::fs::write(&path, TODO_TXT).unwrap();
+    let config = Config { todo_txt: Some(path.clone()), ..Default::default() };
+    let mut app = MyApp::default().with_config(config, None);
+
+    let task = app.todo_list().unwrap().open_tasks()[0].clone();
+    app.select_todo_task(task);
+    assert_eq!(app.task(), "Review PR 42");
+    assert_eq!(app.tag(), "backend");
+
+    app.begin_timer();
+    app.skip_phase();
+    assert_eq!(app.history().records()[0].tag, "backend");
+
+    app.complete_todo_task();
+    assert!(app.todo_task().is_none());
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains(&format!("x {today} Review PR 42 +backend +migration pri:A")));
+}
@@ -0,0 +1,45 @@
+use mypomodoro::TimerPhase;
+use mypomodoro::sound::{Sound, SoundEvent, SoundSlot};
+use mypomodoro::warning::WarningConfig;
+
+#[test]
+fn test_warning_period() {
+    let warning = WarningConfig { enabled: true, minutes: 2.0, breaks: false, ..Default::default() };
+    let pomodoro = 25.0 * 60.0;
+    assert!(!warning.in_warning_period(TimerPhase::Pomodoro, pomodoro, 121.0));
+    assert!(warning.in_warning_period(TimerPhase::Pomodoro, pomodoro, 120.0));
+    assert!(warning.in_warning_period(TimerPhase::Pomodoro, pomodoro, 0.5));
+    assert!(!warning.in_warning_period(TimerPhase::Pomodoro, pomodoro, 0.0));
+    assert!(!warning.in_warning_period(TimerPhase::ShortBreak, 300.0, 60.0));
+
+    // A phase no longer than the warning period would warn as soon as it starts.
+    let breaks = WarningConfig { breaks: true, ..warning.clone() };
+    assert!(breaks.in_warning_period(TimerPhase::ShortBreak, 300.0, 60.0));
+    assert!(!breaks.in_warning_period(TimerPhase::ShortBreak, 120.0, 60.0));
+
+    let disabled = WarningConfig { enabled: false, ..warning };
+    assert!(!disabled.in_warning_period(TimerPhase::Pomodoro, pomodoro, 60.0));
+}
+
+#[test]
+fn test_countdown_seconds() {
+    let warning = WarningConfig { enabled: true, countdown_seconds: 3, ..Default::default() };
+    let seconds: Vec<Option<u32>> = [3.5, 3.0, 2.1, 0.4, 0.0]
+        .iter()
+        .map(|remaining| warning.countdown_second(TimerPhase::LongBreak, *remaining))
+        .collect();
+    assert_eq!(seconds, vec![None, Some(3), Some(3), Some(1), None]);
+    let off = WarningConfig { countdown_seconds: 0, ..warning };
+    assert_eq!(off.countdown_second(TimerPhase::Pomodoro, 0.5), None);
+}
+
+#[test]
+fn test_warning_sounds() {
+    let beep = Sound::beep();
+    assert_eq!(beep.name(), "Beep");
+    assert!(beep.source().is_ok());
+    // The warnings are switched on with the warning settings, so their slots are on but soft.
+    let slot = SoundSlot::default_for(SoundEvent::Warning);
+    assert!(slot.enabled);
+    assert!(slot.volume < SoundSlot::default_for(SoundEvent::PomodoroEnd).volume);
+}